@@ -7,7 +7,7 @@ clap = "4.0"
 //! Comprehensive test script for tool-meister
 //! Run with: cargo run --bin test_runner
 
-use std::process::{exit, Command};
+use std::process::{Command, exit};
 
 fn main() {
     println!("🧪 Running Tool Meister Comprehensive Test Suite");
@@ -1,151 +1,1447 @@
-pub mod install;
-pub mod update;
+pub mod all;
 pub mod build;
-pub mod run;
+pub mod completion;
 pub mod config;
-use crate::config::{Action, Config};
+pub mod history;
+pub mod install;
+pub mod plan;
+pub mod run;
+pub mod update;
+pub mod verify;
+pub mod version;
+use crate::config::{Action, ActionBody, Config};
 use crate::error::Result;
+use crate::global_config::SpawnMode;
+use crate::output::{Marker, Output, Sink, marker};
 use anyhow::Context;
+use futures::stream::{self, StreamExt};
+use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
 use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+/// Flags controlling how actions are executed
+#[derive(Debug, Default, Clone)]
+pub struct ExecOptions {
+    /// Force spawn mode (detach process) even with arguments
+    pub force_spawn: bool,
+    /// Wait for completion even when spawn=true in config
+    pub force_wait: bool,
+    /// Suppress the "Spawned" banner for detached processes
+    pub quiet_spawn: bool,
+    /// Print debug details (e.g. spawned PIDs) to stderr
+    pub verbose: bool,
+    /// Verify (and install if needed) declared dependencies first
+    pub with_deps: bool,
+    /// Print why each spawn-capable action spawns or waits
+    pub explain: bool,
+    /// Run commands exactly as written, skipping `[[...]]` interpolation
+    pub no_interpolate: bool,
+    /// Configured override for the spawn heuristic
+    pub spawn_mode: SpawnMode,
+    /// Print each action's resolved invocation before and after it runs
+    pub trace_commands: bool,
+    /// Directory receiving each step's output as `<seq_id>.log`
+    pub log_dir: Option<PathBuf>,
+    /// Stops the current command and skips the remaining steps once cancelled
+    pub cancel: CancellationToken,
+    /// Attempt every action even after one fails, then report all failures together
+    pub no_fail_fast: bool,
+    /// Where progress messages go (the console unless replaced, e.g. in tests)
+    pub output: Sink,
+    /// Extra variables for every action, applied over the manifest's `env_file`
+    pub env: Vec<(String, String)>,
+    /// Directory `[[workspace.dir]]` expands to; the current directory when unset
+    pub workspace_dir: Option<PathBuf>,
+    /// Run actions in the current directory instead of the tool's directory
+    pub no_cd: bool,
+    /// Print a content hash of the install directory after installing
+    pub content_hash: bool,
+    /// Run the verify actions after installing
+    pub verify: bool,
+    /// Treat actions that don't set `spawn` as spawn-capable (the phase's `spawn_default`)
+    pub spawn_default: bool,
+    /// Spawn every action, recording its PID as `<seq_id>.pid` next to its log in `log_dir`
+    pub detach: bool,
+}
+
+/// SIGINT's number, for platforms without libc's
+#[cfg(not(unix))]
+const SIGINT: i32 = 2;
+
+/// The last SIGINT or SIGTERM received, 0 before any
+static RECEIVED_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+/// Number of waited-on action processes currently running
+static RUNNING_CHILDREN: AtomicUsize = AtomicUsize::new(0);
+
+/// Handle SIGINT and SIGTERM for the rest of the process by cancelling `cancel`: a
+/// waited-on action gets the signal forwarded and the remaining steps are skipped. With
+/// no action running there is nothing to wind down, so the process exits right away.
+pub fn handle_signals(cancel: CancellationToken) -> std::io::Result<()> {
+    #[cfg(unix)]
+    let mut signals = {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        async move || {
+            tokio::select! {
+                _ = terminate.recv() => libc::SIGTERM,
+                _ = interrupt.recv() => libc::SIGINT,
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let mut signals = async || {
+        let _ = tokio::signal::ctrl_c().await;
+        SIGINT
+    };
+
+    tokio::spawn(async move {
+        loop {
+            let signal = signals().await;
+            RECEIVED_SIGNAL.store(signal, Ordering::SeqCst);
+            if RUNNING_CHILDREN.load(Ordering::SeqCst) == 0 {
+                std::process::exit(128 + signal);
+            }
+            cancel.cancel();
+        }
+    });
+    Ok(())
+}
 
+/// Exit status for a process stopped by a signal (128 + its number), if one arrived
+pub fn signal_exit_code() -> Option<u8> {
+    match RECEIVED_SIGNAL.load(Ordering::SeqCst) {
+        0 => None,
+        signal => u8::try_from(128 + signal).ok(),
+    }
+}
 
+/// Counts a waited-on action process as running while alive
+struct RunningChild;
 
+impl RunningChild {
+    fn enter() -> Self {
+        RUNNING_CHILDREN.fetch_add(1, Ordering::SeqCst);
+        RunningChild
+    }
+}
 
+impl Drop for RunningChild {
+    fn drop(&mut self) {
+        RUNNING_CHILDREN.fetch_sub(1, Ordering::SeqCst);
+    }
+}
 
+/// Run `task` on every item with at most `limit` tasks in flight, keeping input order
+pub async fn run_bounded<T, F, Fut>(items: Vec<T>, limit: usize, task: F) -> Vec<Fut::Output>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future,
+{
+    stream::iter(items)
+        .map(task)
+        .buffered(limit.max(1))
+        .collect()
+        .await
+}
 
+/// Run each dependency's `check` command, installing it when the check fails
+async fn ensure_dependencies(config: &Config, options: &ExecOptions) -> Result<()> {
+    let output = &*options.output;
+    for dependency in &config.dependencies {
+        let Some(check) = &dependency.check else {
+            continue;
+        };
+        let check = config.interpolate(check);
+
+        let mut check_cmd = Command::new("sh");
+        check_cmd.arg("-c").arg(&check);
+        match wait_for_command(&mut check_cmd, &check, &options.cancel).await {
+            Ok(()) => {
+                output.line(&format!(
+                    "{} Dependency present: {}",
+                    marker(Marker::Done),
+                    dependency.name
+                ));
+                continue;
+            }
+            Err(crate::error::Error::Cancelled) => return Err(crate::error::Error::Cancelled),
+            Err(_) => {}
+        }
 
+        let Some(install) = &dependency.install else {
+            return Err(crate::error::Error::Command(format!(
+                "Dependency {} is missing (check failed: {})",
+                dependency.name, check
+            )));
+        };
+        let install = config.interpolate(install);
 
+        output.line(&format!("Installing dependency: {}", dependency.name));
+        let mut install_cmd = Command::new("sh");
+        install_cmd.arg("-c").arg(&install);
+        wait_for_command(&mut install_cmd, &install, &options.cancel).await?;
+        output.line(&format!(
+            "{} Installed dependency: {}",
+            marker(Marker::Done),
+            dependency.name
+        ));
+    }
+
+    Ok(())
+}
 
 async fn execute_actions(
     config: &Config,
     actions: &[Action],
     working_dir: Option<&std::path::Path>,
+    install_dir: &std::path::Path,
     extra_args: Option<&[String]>,
-    force_spawn: bool,
-    force_wait: bool,
+    options: &ExecOptions,
 ) -> Result<()> {
+    let install_dir = std::path::absolute(install_dir)
+        .with_context(|| format!("Failed to resolve directory: {}", install_dir.display()))?;
+    let env_vars = load_env_file(config, &install_dir, &*options.output)?;
+    let workspace_dir = match &options.workspace_dir {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir().context("Failed to read the current directory")?,
+    };
+    // `[[env.NAME]]` sees the environment the actions get: `--env`, then `env_file`, then ours
+    let env = |name: &str| {
+        options
+            .env
+            .iter()
+            .rev()
+            .chain(env_vars.iter().rev())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .or_else(|| crate::config::process_env(name))
+    };
+    let interpolate = |text: &str| {
+        if options.no_interpolate {
+            text.to_string()
+        } else {
+            config.interpolate_install(text, &install_dir, &workspace_dir, env)
+        }
+    };
+
+    // Steps skipped on this OS don't count towards the `[n/total]` progress
+    let total = actions
+        .iter()
+        .filter(|action| action.runs_on(std::env::consts::OS))
+        .count();
+    let mut position = 0;
+    let mut failures = Vec::new();
     for action in actions {
-        println!("Step {}: {}", action.seq_id, action.description);
+        if options.cancel.is_cancelled() {
+            return Err(crate::error::Error::Cancelled);
+        }
+        if !action.runs_on(std::env::consts::OS) {
+            options
+                .output
+                .line(&format!("Step {}: {}", action.seq_id, action.description));
+            options.output.line(&format!(
+                "{} Only runs on {}",
+                marker(Marker::Skip),
+                action.os.join(", ")
+            ));
+            continue;
+        }
+        position += 1;
+        options.output.line(&format!(
+            "[{}/{}] Step {}: {}",
+            position, total, action.seq_id, action.description
+        ));
 
-        let interpolated_command = config.interpolate(&action.command);
+        // Everything that can go wrong with a step, setting it up included, is a step failure
+        let step = async {
+            let body = action.body()?;
+            // Built-in git actions always work on the tool's repository, even with `--no-cd`
+            let action_dir = match body {
+                ActionBody::GitClone { .. } => install_dir.parent(),
+                ActionBody::GitPull => Some(install_dir.as_path()),
+                _ => working_dir,
+            };
+            let (full_command, mut cmd) = match body {
+                ActionBody::Command(command) => {
+                    let interpolated_command = interpolate(command);
+                    if interpolated_command.trim().is_empty() {
+                        return Err(crate::error::Error::Config(format!(
+                            "Step {} ({}) has an empty command",
+                            action.seq_id, action.description
+                        )));
+                    }
 
-        // Add extra arguments if provided
-        let full_command = if let Some(args) = extra_args {
-            if args.is_empty() {
-                interpolated_command
-            } else {
-                format!("{} {}", interpolated_command, args.join(" "))
-            }
-        } else {
-            interpolated_command
-        };
+                    let full_command = with_extra_args(interpolated_command, extra_args);
+                    options.output.line(&format!("Executing: {}", full_command));
+                    let cmd = build_command(action, &full_command, env)?;
+                    (full_command, cmd)
+                }
+                ActionBody::Script(script) => {
+                    // Scripts ship next to the manifest that declares them
+                    let script_dir = config
+                        .manifest_dir
+                        .as_deref()
+                        .or(working_dir)
+                        .unwrap_or(std::path::Path::new("."));
+                    let script_path = script_dir.join(config.interpolate_install(
+                        script,
+                        &install_dir,
+                        &workspace_dir,
+                        env,
+                    ));
+                    if !script_path.is_file() {
+                        return Err(crate::error::Error::Config(format!(
+                            "Script not found for step {}: {}",
+                            action.seq_id,
+                            script_path.display()
+                        )));
+                    }
 
-        println!("Executing: {}", full_command);
-
-        let mut cmd = if full_command.starts_with("./") {
-            // Handle relative executable paths
-            let mut command = Command::new("sh");
-            command.arg("-c").arg(&full_command);
-            command
-        } else if full_command.contains(' ') {
-            // Handle commands with arguments
-            let mut command = Command::new("sh");
-            command.arg("-c").arg(&full_command);
-            command
-        } else {
-            // Handle simple commands
-            Command::new(&full_command)
-        };
+                    let full_command =
+                        with_extra_args(script_path.display().to_string(), extra_args);
+                    options.output.line(&format!("Executing: {}", full_command));
+                    let cmd = build_script_command(action, &script_path, extra_args)?;
+                    (full_command, cmd)
+                }
+                ActionBody::GitClone { url, branch } => {
+                    let git_args = git_clone_args(config, url, branch, interpolate)?;
+                    git_invocation(git_args, extra_args, &*options.output)
+                }
+                ActionBody::GitPull => {
+                    git_invocation(vec!["pull".to_string()], extra_args, &*options.output)
+                }
+            };
 
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
+            if let Some(dir) = action_dir {
+                cmd.current_dir(dir);
+            }
+            cmd.envs(env_vars.iter().map(|(key, value)| (key, value)));
+            cmd.envs(options.env.iter().map(|(key, value)| (key, value)));
 
-        if action.spawn {
-            // Determine spawn behavior based on flags and arguments
-            let should_spawn = if force_wait {
-                false // --wait flag overrides everything
-            } else if force_spawn {
-                true // --spawn flag forces spawning
-            } else {
-                // Smart default: spawn only if no args, or if args look like they won't produce output
-                let has_args = extra_args.is_some_and(|args| !args.is_empty());
-                if !has_args {
-                    true // No args, likely GUI launch
-                } else {
-                    // Check if args suggest this is informational (will produce output and exit)
-                    let config_info_args: Vec<&str> =
-                        config.info_args.iter().map(|s| s.as_str()).collect();
-                    let default_info_args = ["--help", "-h", "--version", "-V", "--list", "--show"];
-
-                    // Use config info_args if provided, otherwise use defaults
-                    let info_args = if config_info_args.is_empty() {
-                        &default_info_args[..]
-                    } else {
-                        &config_info_args[..]
-                    };
-
-                    let has_info_arg = extra_args
-                        .unwrap_or(&[])
-                        .iter()
-                        .any(|arg| info_args.contains(&arg.as_str()));
-                    !has_info_arg // Spawn unless it's an info command
-                }
+            let decision = (action.spawns(options.spawn_default) || options.detach)
+                .then(|| spawn_decision(config, extra_args, options));
+            if options.explain
+                && let Some(decision) = &decision
+            {
+                options.output.line(&format!(
+                    "[explain] Step {}: {}",
+                    action.seq_id,
+                    decision.reason()
+                ));
+            }
+            if options.trace_commands {
+                options.output.diagnostic(&trace_line(action, &cmd));
+            }
+
+            let log_path = match &options.log_dir {
+                Some(dir) => Some(step_log_path(dir, action)?),
+                None => None,
             };
 
-            if should_spawn {
-                // Spawn mode: detach process
-                cmd.stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .stdin(Stdio::null());
+            if decision.as_ref().is_some_and(SpawnDecision::spawns) {
+                // Spawn mode: detach process, sending its output to the step log if there is one
+                match &log_path {
+                    Some(path) => {
+                        let log = std::fs::File::create(path).with_context(|| {
+                            format!("Failed to create log file: {}", path.display())
+                        })?;
+                        cmd.stdout(log.try_clone()?).stderr(log)
+                    }
+                    None => cmd.stdout(Stdio::null()).stderr(Stdio::null()),
+                }
+                .stdin(Stdio::null());
 
                 let child = cmd
                     .spawn()
                     .with_context(|| format!("Failed to spawn command: {}", full_command))?;
+                let pid = child.id().unwrap_or(0);
+                if options.trace_commands {
+                    options.output.diagnostic(&format!(
+                        "[trace] step {} spawned: pid={}",
+                        action.seq_id, pid
+                    ));
+                }
 
-                println!(
-                    "✓ Spawned: {} (PID: {})\n",
-                    action.description,
-                    child.id().unwrap_or(0)
-                );
+                if options.verbose {
+                    options.output.diagnostic(&format!(
+                        "[debug] spawned '{}' with PID {}",
+                        full_command, pid
+                    ));
+                }
+
+                if options.detach
+                    && let Some(log_path) = &log_path
+                {
+                    let pid_path = log_path.with_extension("pid");
+                    std::fs::write(&pid_path, format!("{}\n", pid)).with_context(|| {
+                        format!("Failed to write PID file: {}", pid_path.display())
+                    })?;
+                    options.output.line(&format!("Log: {}", log_path.display()));
+                    options
+                        .output
+                        .line(&format!("PID file: {}", pid_path.display()));
+                }
+
+                if !options.quiet_spawn {
+                    options.output.line(&format!(
+                        "{} Spawned: {} (PID: {})\n",
+                        marker(Marker::Done),
+                        action.description,
+                        pid
+                    ));
+                }
             } else {
-                // Wait mode: show output and wait for completion
-                
+                // Wait mode: run to completion
+                let started = std::time::Instant::now();
+                let result = wait_for_action(
+                    action,
+                    &mut cmd,
+                    &full_command,
+                    log_path.as_deref(),
+                    &options.cancel,
+                )
+                .await;
+                if options.trace_commands {
+                    options.output.diagnostic(&format!(
+                        "[trace] step {} done: {} in {}ms",
+                        action.seq_id,
+                        if result.is_ok() { "ok" } else { "failed" },
+                        started.elapsed().as_millis()
+                    ));
+                }
+                result?;
+                options.output.line(&format!(
+                    "{} Completed: {}\n",
+                    marker(Marker::Done),
+                    action.description
+                ));
+            }
+            Ok::<(), crate::error::Error>(())
+        };
+        match step.await {
+            Err(e) if options.no_fail_fast && !matches!(e, crate::error::Error::Cancelled) => {
+                options.output.diagnostic(&format!(
+                    "{} Step {} ({}) failed; continuing: {}",
+                    marker(Marker::Fail),
+                    action.seq_id,
+                    action.description,
+                    e
+                ));
+                failures.push(format!(
+                    "Step {} ({}): {}",
+                    action.seq_id, action.description, e
+                ));
+            }
+            result => result?,
+        }
+    }
 
-                let output = cmd
-                    .output()
-                    .await
-                    .with_context(|| format!("Failed to execute command: {}", full_command))?;
+    if !failures.is_empty() {
+        return Err(crate::error::Error::Command(format!(
+            "{} of {} steps failed:\n  {}",
+            failures.len(),
+            total,
+            failures.join("\n  ")
+        )));
+    }
 
-                if !output.status.success() {
-                    return Err(crate::error::Error::Command(format!("Command failed:здравствуйте {}
+    Ok(())
+}
 
--- stdout --
-{}
--- stderr --
-{}", full_command, String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))));
-                }
+/// Variables from the manifest's `env_file`, resolved against the tool's directory.
+///
+/// A missing file only warns unless the manifest sets `env_file_required`.
+fn load_env_file(
+    config: &Config,
+    install_dir: &std::path::Path,
+    output: &dyn Output,
+) -> Result<Vec<(String, String)>> {
+    let Some(env_file) = &config.env_file else {
+        return Ok(Vec::new());
+    };
+    let path = install_dir.join(config.interpolate(env_file));
+
+    if !path.is_file() {
+        if config.env_file_required {
+            return Err(crate::error::Error::Config(format!(
+                "Required env file not found: {}",
+                path.display()
+            )));
+        }
+        output.diagnostic(&format!(
+            "{} Env file not found, continuing without it: {}",
+            marker(Marker::Warn),
+            path.display()
+        ));
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read env file: {}", path.display()))?;
+    Ok(crate::config::parse_env_file(&content))
+}
+
+/// `set -x`-style description of the process an action is about to start
+fn trace_line(action: &Action, cmd: &Command) -> String {
+    let cmd = cmd.as_std();
+    let cwd = cmd
+        .get_current_dir()
+        .map(std::path::Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok())
+        .map_or_else(|| "?".to_string(), |dir| dir.display().to_string());
+    let program = cmd.get_program().to_string_lossy();
+    let shell = match &action.shell {
+        Some(shell) => shell.as_str(),
+        None if program == "sh" => "sh",
+        None => "none",
+    };
+    let argv: Vec<_> = std::iter::once(program.clone())
+        .chain(cmd.get_args().map(|arg| arg.to_string_lossy()))
+        .collect();
+    // Only the names: values set through --env or env_file are often credentials
+    let env_keys: Vec<_> = cmd
+        .get_envs()
+        .map(|(key, _)| key.to_string_lossy())
+        .collect();
+
+    format!(
+        "[trace] step {} start: cwd={} shell={:?} argv={:?} env_keys={:?}",
+        action.seq_id, cwd, shell, argv, env_keys
+    )
+}
+
+/// Append extra arguments to a command line
+fn with_extra_args(command: String, extra_args: Option<&[String]>) -> String {
+    match extra_args {
+        Some(args) if !args.is_empty() => format!("{} {}", command, args.join(" ")),
+        _ => command,
+    }
+}
+
+/// Arguments for a `git_clone` action: the manifest's repo url and default branch unless
+/// overridden, cloned into the tool's directory name
+fn git_clone_args(
+    config: &Config,
+    url: Option<&str>,
+    branch: Option<&str>,
+    resolve: impl Fn(&str) -> String,
+) -> Result<Vec<String>> {
+    let target = config.repo_dir(std::path::Path::new(""))?;
+
+    Ok(vec![
+        "clone".to_string(),
+        "--branch".to_string(),
+        branch.map_or_else(|| config.resolved_ref().to_string(), &resolve),
+        url.map_or_else(|| config.repo.url.clone(), &resolve),
+        target.display().to_string(),
+    ])
+}
+
+/// Run git directly, without a shell, and print the equivalent command line
+fn git_invocation(
+    mut git_args: Vec<String>,
+    extra_args: Option<&[String]>,
+    output: &dyn Output,
+) -> (String, Command) {
+    git_args.extend(extra_args.unwrap_or(&[]).iter().cloned());
+    let full_command = format!("git {}", git_args.join(" "));
+    output.line(&format!("Executing: {}", full_command));
+
+    let mut command = Command::new("git");
+    command.args(&git_args);
+    (full_command, command)
+}
+
+/// Split an action's `shell` override into its program and arguments
+fn shell_parts(action: &Action, shell: &str) -> Result<(String, Vec<String>)> {
+    let mut parts = shell.split_whitespace();
+    let program = parts.next().ok_or_else(|| {
+        crate::error::Error::Config(format!(
+            "Empty shell for step {}: {}",
+            action.seq_id, action.description
+        ))
+    })?;
+    if !program_exists(program) {
+        return Err(crate::error::Error::Config(format!(
+            "Shell not found for step {}: {}",
+            action.seq_id, program
+        )));
+    }
 
-                println!("✓ Completed: {}
-", action.description);
+    Ok((program.to_string(), parts.map(String::from).collect()))
+}
+
+/// Build the process for an action, honoring its `shell` override. `env` resolves the
+/// variables of a command run without a shell.
+fn build_command(
+    action: &Action,
+    full_command: &str,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Command> {
+    if let Some(shell) = &action.shell {
+        let (program, shell_args) = shell_parts(action, shell)?;
+        let mut command = Command::new(program);
+        if shell_args.is_empty() {
+            // A bare shell name like "bash" takes the command via -c
+            command.arg("-c");
+        } else {
+            command.args(shell_args);
+        }
+        command.arg(full_command);
+        return Ok(command);
+    }
+
+    let command = if full_command.starts_with("./") {
+        // Handle relative executable paths
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(full_command);
+        command
+    } else if full_command.contains(' ') {
+        // Handle commands with arguments
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(full_command);
+        command
+    } else {
+        // Handle simple commands; without a shell we expand `~` and `$VAR` ourselves
+        Command::new(expand_env(full_command, env))
+    };
+    Ok(command)
+}
+
+/// Build the process running a script file through the action's shell (`sh` by default)
+///
+/// Only the shell's program is used; arguments like `-c` apply to inline commands.
+fn build_script_command(
+    action: &Action,
+    script_path: &std::path::Path,
+    extra_args: Option<&[String]>,
+) -> Result<Command> {
+    let program = match &action.shell {
+        Some(shell) => shell_parts(action, shell)?.0,
+        None => "sh".to_string(),
+    };
+
+    let mut command = Command::new(program);
+    command.arg(script_path).args(extra_args.unwrap_or(&[]));
+    Ok(command)
+}
+
+/// Expand a leading `~` and `$VAR`/`${VAR}` references like a shell would
+fn expand_env(text: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let text = match text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", lookup("HOME").unwrap_or_default(), rest)
+        }
+        _ => text.to_string(),
+    };
+
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+
+        let braced = chars.next_if_eq(&'{').is_some();
+        let mut name = String::new();
+        while let Some(next) = chars.next_if(|ch| ch.is_ascii_alphanumeric() || *ch == '_') {
+            name.push(next);
+        }
+        if braced && chars.next_if_eq(&'}').is_none() {
+            // Unterminated `${`, keep it literally
+            result.push_str("${");
+            result.push_str(&name);
+        } else if name.is_empty() {
+            result.push('$');
+            if braced {
+                result.push_str("{}");
             }
         } else {
-            
+            result.push_str(&lookup(&name).unwrap_or_default());
+        }
+    }
+    result
+}
 
-            let output = cmd
-                .output()
-                .await
-                .with_context(|| format!("Failed to execute command: {}", full_command))?;
+/// Check whether a program is an existing path or can be found on PATH
+fn program_exists(program: &str) -> bool {
+    if program.contains('/') {
+        return std::path::Path::new(program).is_file();
+    }
+
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// Why a `spawn` action detaches or waits
+#[derive(Debug, PartialEq)]
+enum SpawnDecision {
+    ForceWait,
+    Detach,
+    ForceSpawn,
+    AlwaysSpawn,
+    NeverSpawn,
+    NoArgs,
+    InfoArg(String),
+    NoInfoArgs,
+}
+
+impl SpawnDecision {
+    fn spawns(&self) -> bool {
+        !matches!(
+            self,
+            SpawnDecision::ForceWait | SpawnDecision::NeverSpawn | SpawnDecision::InfoArg(_)
+        )
+    }
 
-            if !output.status.success() {
-                return Err(crate::error::Error::Command(format!("Command failed:здравствуйте {}\n\n-- stdout --\n{}\n-- stderr --\n{}", full_command, String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))));
+    fn reason(&self) -> String {
+        match self {
+            SpawnDecision::ForceWait => "waiting: --wait given".to_string(),
+            SpawnDecision::Detach => "spawning: --detach given".to_string(),
+            SpawnDecision::ForceSpawn => "spawning: --spawn given".to_string(),
+            SpawnDecision::AlwaysSpawn => "spawning: spawn_mode is \"always\"".to_string(),
+            SpawnDecision::NeverSpawn => "waiting: spawn_mode is \"never\"".to_string(),
+            SpawnDecision::NoArgs => "spawning: no arguments, likely a GUI launch".to_string(),
+            SpawnDecision::InfoArg(arg) => format!("waiting: info argument detected ({})", arg),
+            SpawnDecision::NoInfoArgs => {
+                "spawning: no info arguments among the arguments".to_string()
             }
+        }
+    }
+}
 
-            println!("✓ Completed: {}
-", action.description);
+/// Decide whether a `spawn` action should detach based on flags and arguments
+fn spawn_decision(
+    config: &Config,
+    extra_args: Option<&[String]>,
+    options: &ExecOptions,
+) -> SpawnDecision {
+    if options.force_wait {
+        return SpawnDecision::ForceWait; // --wait flag overrides everything
+    }
+    if options.detach {
+        return SpawnDecision::Detach;
+    }
+    if options.force_spawn {
+        return SpawnDecision::ForceSpawn; // --spawn flag forces spawning
+    }
+    match options.spawn_mode {
+        SpawnMode::Always => return SpawnDecision::AlwaysSpawn,
+        SpawnMode::Never => return SpawnDecision::NeverSpawn,
+        SpawnMode::Auto => {}
+    }
+
+    // Smart default: spawn only if no args, or if args look like they won't produce output
+    let args = extra_args.unwrap_or(&[]);
+    if args.is_empty() {
+        return SpawnDecision::NoArgs; // No args, likely GUI launch
+    }
+
+    // Check if args suggest this is informational (will produce output and exit)
+    let config_info_args: Vec<&str> = config.info_args.iter().map(|s| s.as_str()).collect();
+    let default_info_args = ["--help", "-h", "--version", "-V", "--list", "--show"];
+
+    // Use config info_args if provided, otherwise use defaults
+    let info_args = if config_info_args.is_empty() {
+        &default_info_args[..]
+    } else {
+        &config_info_args[..]
+    };
+
+    // Spawn unless it's an info command
+    match args.iter().find(|arg| {
+        is_info_arg(
+            arg,
+            info_args,
+            config.info_args_prefix,
+            config.case_insensitive_info_args,
+        )
+    }) {
+        Some(arg) => SpawnDecision::InfoArg(arg.clone()),
+        None => SpawnDecision::NoInfoArgs,
+    }
+}
+
+/// Match an argument against info args exactly, or by prefix when enabled,
+/// optionally ignoring case
+fn is_info_arg(arg: &str, info_args: &[&str], prefix: bool, ignore_case: bool) -> bool {
+    let fold = |text: &str| {
+        if ignore_case {
+            text.to_lowercase()
+        } else {
+            text.to_string()
+        }
+    };
+    let arg = fold(arg);
+    info_args
+        .iter()
+        .map(|info_arg| fold(info_arg))
+        .any(|info_arg| arg == info_arg || (prefix && arg.starts_with(&info_arg)))
+}
+
+/// Run an action's command to completion, attaching the terminal when it's interactive
+/// and killing it once its timeout passes
+async fn wait_for_action(
+    action: &Action,
+    cmd: &mut Command,
+    full_command: &str,
+    log_path: Option<&std::path::Path>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let run = async {
+        if !action.interactive {
+            return wait_and_log(cmd, full_command, log_path, cancel).await;
+        }
+
+        // Interactive output goes straight to the terminal, so there is nothing to log
+        let _running = RunningChild::enter();
+        let mut child = cmd
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to execute command: {}", full_command))?;
+        let status = tokio::select! {
+            status = child.wait() => status,
+            _ = cancel.cancelled() => {
+                stop_interactive(&mut child).await;
+                return Err(crate::error::Error::Cancelled);
+            }
+        }
+        .with_context(|| format!("Failed to execute command: {}", full_command))?;
+        if !status.success() {
+            return Err(crate::error::Error::Command(format!(
+                "Command failed: {} ({})",
+                full_command, status
+            )));
+        }
+        Ok(())
+    };
+
+    match action.timeout_secs {
+        Some(secs) => tokio::time::timeout(std::time::Duration::from_secs(secs), run)
+            .await
+            .unwrap_or_else(|_| {
+                Err(crate::error::Error::Command(format!(
+                    "Command timed out after {}s: {}",
+                    secs, full_command
+                )))
+            }),
+        None => run.await,
+    }
+}
+
+/// Stop a cancelled interactive action and wait for it to exit. It shares our terminal, so
+/// a Ctrl-C reached it already; SIGTERM is passed on and a plain cancellation kills it.
+async fn stop_interactive(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    match (RECEIVED_SIGNAL.load(Ordering::SeqCst), child.id()) {
+        (libc::SIGINT, _) => {}
+        (libc::SIGTERM, Some(pid)) => {
+            // SAFETY: kill(2) only takes plain integers
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+        }
+        _ => {
+            let _ = child.start_kill();
+        }
+    }
+    #[cfg(not(unix))]
+    if RECEIVED_SIGNAL.load(Ordering::SeqCst) != SIGINT {
+        let _ = child.start_kill();
+    }
+    let _ = child.wait().await;
+}
+
+/// Run a command to completion, returning its captured output on failure
+async fn wait_for_command(
+    cmd: &mut Command,
+    full_command: &str,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    wait_and_log(cmd, full_command, None, cancel).await
+}
+
+/// Log file for an action's output under `log_dir`, creating the directory
+fn step_log_path(log_dir: &std::path::Path, action: &Action) -> Result<PathBuf> {
+    std::fs::create_dir_all(log_dir)
+        .with_context(|| format!("Failed to create log directory: {}", log_dir.display()))?;
+    Ok(log_dir.join(format!("{}.log", action.seq_id)))
+}
+
+/// Like `wait_for_command`, also writing the captured output to `log_path`
+async fn wait_and_log(
+    cmd: &mut Command,
+    full_command: &str,
+    log_path: Option<&std::path::Path>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    // A cancelled run (e.g. restarted by --watch) must not leave the process behind
+    cmd.kill_on_drop(true)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // In its own process group the child only sees the signals we forward, exactly once
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let _running = RunningChild::enter();
+    let child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to execute command: {}", full_command))?;
+    let (output, interrupted) = match wait_forwarding_signals(child, cancel).await {
+        Ok(waited) => waited,
+        Err(_) if cancel.is_cancelled() => return Err(crate::error::Error::Cancelled),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to execute command: {}", full_command))?;
         }
+    };
+
+    if let Some(path) = log_path {
+        let mut log = output.stdout.clone();
+        log.extend_from_slice(&output.stderr);
+        std::fs::write(path, log)
+            .with_context(|| format!("Failed to write log file: {}", path.display()))?;
+    }
+
+    if interrupted {
+        return Err(crate::error::Error::Cancelled);
+    }
+    if !output.status.success() {
+        return Err(crate::error::Error::Command(format!(
+            "Command failed:здравствуйте {}\n\n-- stdout --\n{}\n-- stderr --\n{}",
+            full_command,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )));
     }
 
     Ok(())
 }
+
+/// Wait for `child`; once `cancel` fires, pass the signal that cancelled the run on to its
+/// process group (killing it when no signal did) so it can shut down gracefully. Also
+/// returns whether the child was stopped that way.
+#[cfg(unix)]
+async fn wait_forwarding_signals(
+    child: tokio::process::Child,
+    cancel: &CancellationToken,
+) -> std::io::Result<(std::process::Output, bool)> {
+    let pgid = child.id();
+    let output = child.wait_with_output();
+    tokio::pin!(output);
+
+    tokio::select! {
+        output = &mut output => return Ok((output?, false)),
+        _ = cancel.cancelled() => {}
+    }
+    let signal = match RECEIVED_SIGNAL.load(Ordering::SeqCst) {
+        0 => libc::SIGKILL,
+        signal => signal,
+    };
+    if let Some(pgid) = pgid {
+        // SAFETY: kill(2) only takes plain integers
+        unsafe { libc::kill(-(pgid as libc::pid_t), signal) };
+    }
+    Ok((output.await?, true))
+}
+
+#[cfg(not(unix))]
+async fn wait_forwarding_signals(
+    child: tokio::process::Child,
+    cancel: &CancellationToken,
+) -> std::io::Result<(std::process::Output, bool)> {
+    // Dropping the wait kills the child (kill_on_drop)
+    tokio::select! {
+        output = child.wait_with_output() => Ok((output?, false)),
+        _ = cancel.cancelled() => Err(std::io::ErrorKind::Interrupted.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(info_args_prefix: bool) -> Config {
+        let manifest = format!(
+            r#"{{
+  "repo": {{ "name": "tool", "url": "https://example.com/tool.git", "default_branch": {{ "name": "main" }} }},
+  "dependencies": [],
+  "actions": {{ "installation": [], "update": [], "build": [], "run": [] }},
+  "info_args_prefix": {}
+}}"#,
+            info_args_prefix
+        );
+        serde_json::from_str(&manifest).unwrap()
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_should_spawn_exact_info_arg_waits() {
+        let config = test_config(false);
+        let args = args(&["--help"]);
+        assert!(!spawn_decision(&config, Some(&args), &ExecOptions::default()).spawns());
+    }
+
+    #[test]
+    fn test_should_spawn_prefix_info_arg_waits_when_enabled() {
+        let args = args(&["--help=json"]);
+        assert!(!spawn_decision(&test_config(true), Some(&args), &ExecOptions::default()).spawns());
+        assert!(spawn_decision(&test_config(false), Some(&args), &ExecOptions::default()).spawns());
+    }
+
+    #[test]
+    fn test_should_spawn_info_arg_ignores_case_when_enabled() {
+        let args = args(&["--Help"]);
+        let options = ExecOptions::default();
+        assert!(spawn_decision(&test_config(false), Some(&args), &options).spawns());
+
+        let mut config = test_config(false);
+        config.case_insensitive_info_args = true;
+        assert!(!spawn_decision(&config, Some(&args), &options).spawns());
+
+        config.info_args = vec!["/?".to_string()];
+        assert!(!spawn_decision(&config, Some(&["/?".to_string()]), &options).spawns());
+    }
+
+    #[test]
+    fn test_should_spawn_non_info_arg_spawns() {
+        let config = test_config(true);
+        let args = args(&["project.txt"]);
+        assert!(spawn_decision(&config, Some(&args), &ExecOptions::default()).spawns());
+    }
+
+    #[tokio::test]
+    async fn test_execute_actions_rejects_empty_command() {
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 7, "description": "print name", "command": "[[repo.name]]" }],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+
+        let result = execute_actions(
+            &config,
+            &config.actions.installation,
+            None,
+            std::path::Path::new("tool"),
+            None,
+            &ExecOptions::default(),
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, crate::error::Error::Config(_)));
+        assert!(
+            err.to_string()
+                .contains("Step 7 (print name) has an empty command")
+        );
+    }
+
+    #[test]
+    fn test_expand_env_home_and_vars() {
+        let lookup = |name: &str| match name {
+            "HOME" => Some("/home/user".to_string()),
+            "TOOL_DIR" => Some("/opt/tools".to_string()),
+            _ => None,
+        };
+
+        assert_eq!(expand_env("~/bin/tool", lookup), "/home/user/bin/tool");
+        assert_eq!(expand_env("$HOME/bin/tool", lookup), "/home/user/bin/tool");
+        assert_eq!(expand_env("${TOOL_DIR}/tool", lookup), "/opt/tools/tool");
+        assert_eq!(expand_env("$MISSING/tool", lookup), "/tool");
+        assert_eq!(expand_env("~other/tool", lookup), "~other/tool");
+        assert_eq!(expand_env("cost$", lookup), "cost$");
+    }
+
+    async fn max_in_flight(limit: usize) -> usize {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let results = run_bounded((0..8).collect(), limit, |item: usize| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                item * 2
+            }
+        })
+        .await;
+
+        assert_eq!(results, (0..8).map(|item| item * 2).collect::<Vec<_>>());
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_serializes_with_one_job() {
+        assert_eq!(max_in_flight(1).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_allows_parallelism_up_to_limit() {
+        assert_eq!(max_in_flight(4).await, 4);
+    }
+
+    #[test]
+    fn test_spawn_mode_always_spawns_with_info_args() {
+        let options = ExecOptions {
+            spawn_mode: SpawnMode::Always,
+            ..Default::default()
+        };
+        let args = args(&["--help"]);
+        let decision = spawn_decision(&test_config(false), Some(&args), &options);
+        assert_eq!(decision, SpawnDecision::AlwaysSpawn);
+        assert!(decision.spawns());
+    }
+
+    #[test]
+    fn test_spawn_mode_never_waits_without_args() {
+        let options = ExecOptions {
+            spawn_mode: SpawnMode::Never,
+            ..Default::default()
+        };
+        let decision = spawn_decision(&test_config(false), None, &options);
+        assert_eq!(decision, SpawnDecision::NeverSpawn);
+        assert!(!decision.spawns());
+
+        let forced = ExecOptions {
+            force_spawn: true,
+            ..options
+        };
+        assert!(spawn_decision(&test_config(false), None, &forced).spawns());
+    }
+
+    #[test]
+    fn test_trace_line_omits_env_values() {
+        let action: Action = serde_json::from_str(
+            r#"{ "seq-id": 3, "description": "deploy", "command": "deploy" }"#,
+        )
+        .unwrap();
+        let mut cmd = Command::new("sh");
+        cmd.env("API_TOKEN", "s3cret");
+
+        let line = trace_line(&action, &cmd);
+        assert!(line.contains(r#"env_keys=["API_TOKEN"]"#), "{}", line);
+        assert!(!line.contains("s3cret"), "{}", line);
+    }
+
+    #[test]
+    fn test_git_clone_args_default_to_manifest_repo() {
+        let config = test_config(false);
+        assert_eq!(
+            git_clone_args(&config, None, None, |text| config.interpolate(text)).unwrap(),
+            args(&[
+                "clone",
+                "--branch",
+                "main",
+                "https://example.com/tool.git",
+                "tool"
+            ])
+        );
+
+        let overridden =
+            git_clone_args(&config, Some("[[repo.url]].mirror"), Some("dev"), |text| {
+                config.interpolate(text)
+            })
+            .unwrap();
+        assert_eq!(
+            overridden,
+            args(&[
+                "clone",
+                "--branch",
+                "dev",
+                "https://example.com/tool.git.mirror",
+                "tool"
+            ])
+        );
+
+        // A pinned ref (from the manifest or `install --ref`) wins over the default branch
+        let mut pinned = test_config(false);
+        pinned.repo.git_ref = Some("v1.2.0".to_string());
+        assert_eq!(
+            git_clone_args(&pinned, None, None, |text| pinned.interpolate(text)).unwrap()[2],
+            "v1.2.0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_typed_shell_action_runs_command() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "touch", "type": "shell", "command": "touch [[repo.name]].done" }],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+
+        execute_actions(
+            &config,
+            &config.actions.installation,
+            Some(temp_dir.path()),
+            std::path::Path::new("tool"),
+            None,
+            &ExecOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(temp_dir.path().join("tool.done").exists());
+    }
+
+    #[tokio::test]
+    async fn test_install_dir_interpolates_to_absolute_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [], "update": [], "run": [],
+    "build": [{ "seq-id": 1, "description": "configure", "command": "printf %s --prefix=[[install.dir]] > configured" }]
+  }
+}"#,
+        )
+        .unwrap();
+        let install_dir = temp_dir.path().join("tool");
+
+        execute_actions(
+            &config,
+            &config.actions.build,
+            Some(temp_dir.path()),
+            &install_dir,
+            None,
+            &ExecOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let configured = std::fs::read_to_string(temp_dir.path().join("configured")).unwrap();
+        assert_eq!(configured, format!("--prefix={}", install_dir.display()));
+    }
+
+    #[tokio::test]
+    async fn test_env_tokens_see_the_action_environment() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "greet", "command": "printf '%s $HOME' [[env.HOME]] > seen" }],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+        let options = ExecOptions {
+            env: vec![("HOME".to_string(), "/from/option".to_string())],
+            ..Default::default()
+        };
+
+        execute_actions(
+            &config,
+            &config.actions.installation,
+            Some(temp_dir.path()),
+            std::path::Path::new("tool"),
+            None,
+            &options,
+        )
+        .await
+        .unwrap();
+
+        // The token takes the `--env` value; single-quoted `$HOME` reaches the shell untouched
+        let seen = std::fs::read_to_string(temp_dir.path().join("seen")).unwrap();
+        assert_eq!(seen, "/from/option $HOME");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_single_word_command_expands_env_option() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let program = temp_dir.path().join("mark.sh");
+        std::fs::write(&program, "#!/bin/sh\ntouch marked\n").unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "mark", "command": "$TOOL_MEISTER_TEST_BIN" }],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+        // Set only through `--env`, never in our own environment
+        let options = ExecOptions {
+            env: vec![(
+                "TOOL_MEISTER_TEST_BIN".to_string(),
+                program.display().to_string(),
+            )],
+            ..Default::default()
+        };
+
+        execute_actions(
+            &config,
+            &config.actions.installation,
+            Some(temp_dir.path()),
+            std::path::Path::new("tool"),
+            None,
+            &options,
+        )
+        .await
+        .unwrap();
+
+        assert!(temp_dir.path().join("marked").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_current_and_remaining_actions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "slow", "command": "sleep 5" },
+      { "seq-id": 2, "description": "touch", "command": "touch after-cancel" }
+    ],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+        let options = ExecOptions::default();
+        let cancel = options.cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            cancel.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = execute_actions(
+            &config,
+            &config.actions.installation,
+            Some(temp_dir.path()),
+            std::path::Path::new("tool"),
+            None,
+            &options,
+        )
+        .await;
+
+        assert!(matches!(result, Err(crate::error::Error::Cancelled)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(!temp_dir.path().join("after-cancel").exists());
+    }
+
+    #[tokio::test]
+    async fn test_no_fail_fast_runs_every_action_and_aggregates_failures() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "lint", "command": "touch lint.ran && false" },
+      { "seq-id": 2, "description": "check", "command": "true" },
+      { "seq-id": 3, "description": "test", "command": "touch test.ran && false" }
+    ],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+        let options = ExecOptions {
+            no_fail_fast: true,
+            ..Default::default()
+        };
+
+        let message = execute_actions(
+            &config,
+            &config.actions.installation,
+            Some(temp_dir.path()),
+            std::path::Path::new("tool"),
+            None,
+            &options,
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+        assert!(temp_dir.path().join("lint.ran").exists());
+        assert!(temp_dir.path().join("test.ran").exists());
+        assert!(message.contains("2 of 3 steps failed"), "{}", message);
+        assert!(message.contains("Step 1 (lint)"), "{}", message);
+        assert!(message.contains("Step 3 (test)"), "{}", message);
+        assert!(!message.contains("Step 2 (check)"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_no_fail_fast_records_step_setup_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "missing script", "script": "missing.sh" },
+      { "seq-id": 2, "description": "blank", "command": "  " },
+      { "seq-id": 3, "description": "conflict", "command": "true", "script": "setup.sh" },
+      { "seq-id": 4, "description": "last", "command": "touch last.ran" }
+    ],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+        let options = ExecOptions {
+            no_fail_fast: true,
+            ..Default::default()
+        };
+
+        let message = execute_actions(
+            &config,
+            &config.actions.installation,
+            Some(temp_dir.path()),
+            std::path::Path::new("tool"),
+            None,
+            &options,
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+        assert!(temp_dir.path().join("last.ran").exists());
+        assert!(message.contains("3 of 4 steps failed"), "{}", message);
+        assert!(
+            message.contains("Script not found for step 1"),
+            "{}",
+            message
+        );
+        assert!(message.contains("Step 2 (blank)"), "{}", message);
+        assert!(message.contains("Step 3 (conflict)"), "{}", message);
+    }
+}
@@ -0,0 +1,89 @@
+use super::ExecOptions;
+use crate::config::{Actions, Config};
+use crate::error::{Error, Result};
+use crate::global_config::GlobalConfig;
+use crate::output::{Marker, marker};
+
+/// Whether `all` runs `phase`; verification stays with its own `verify` command
+pub fn runs_phase(phase: &str) -> bool {
+    phase != "verify"
+}
+
+/// Run the installation, update, build and run phases in `Actions::PHASES` order,
+/// stopping at the first failure unless `keep_going` is set. Prints a per-phase
+/// summary at the end.
+pub async fn all_command(
+    config: &Config,
+    args: &[String],
+    keep_going: bool,
+    options: &ExecOptions,
+    global_config: &GlobalConfig,
+) -> Result<()> {
+    let mut results: Vec<(&str, Option<bool>)> = Vec::new();
+    let mut stopped = false;
+
+    for phase in Actions::PHASES
+        .into_iter()
+        .filter(|phase| runs_phase(phase))
+    {
+        if stopped {
+            results.push((phase, None));
+            continue;
+        }
+
+        let result = match phase {
+            "installation" => {
+                super::install::install_command(config, global_config, Default::default(), options)
+                    .await
+            }
+            "update" => super::update::update_command(config, global_config, options).await,
+            "build" => super::build::build_command(config, global_config, options).await,
+            "run" => super::run::run_command(config, args, options, global_config).await,
+            other => unreachable!("no command runs the {} phase", other),
+        };
+
+        if let Err(e) = &result {
+            options.output.diagnostic(&format!(
+                "{} Phase {} failed: {}",
+                marker(Marker::Fail),
+                phase,
+                e
+            ));
+            stopped = !keep_going;
+        }
+        results.push((phase, Some(result.is_ok())));
+    }
+
+    options
+        .output
+        .line(&format!("Phase summary for {}:", config.repo.name));
+    for (phase, outcome) in &results {
+        match outcome {
+            Some(true) => options
+                .output
+                .line(&format!("  {} {}", marker(Marker::Ok), phase)),
+            Some(false) => options
+                .output
+                .line(&format!("  {} {}", marker(Marker::Fail), phase)),
+            None => options
+                .output
+                .line(&format!("  {} {} (skipped)", marker(Marker::Skip), phase)),
+        }
+    }
+
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|(_, outcome)| *outcome == Some(false))
+        .map(|(phase, _)| *phase)
+        .collect();
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Command(format!(
+            "Failed phases for {}: {}",
+            config.repo.name,
+            failed.join(", ")
+        )))
+    }
+}
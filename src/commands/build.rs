@@ -2,27 +2,38 @@ use crate::config::Config;
 use crate::error::Result;
 use crate::global_config::GlobalConfig;
 
-pub async fn build_command(config: &Config, global_config: &GlobalConfig) -> Result<()> {
-    println!("Building {}...", config.repo.name);
+pub async fn build_command(
+    config: &Config,
+    global_config: &GlobalConfig,
+    options: &super::ExecOptions,
+) -> Result<()> {
+    options
+        .output
+        .line(&format!("Building {}...", config.repo.name));
 
     let tools_dir = global_config.get_tools_directory();
-    let repo_dir = tools_dir.join(&config.repo.name);
+    let repo_dir = config.repo_dir(&tools_dir)?;
 
     if !repo_dir.exists() {
-        println!(
+        options.output.line(&format!(
             "Repository {} does not exist. Use 'install' command first.",
             config.repo.name
-        );
+        ));
         return Ok(());
     }
 
     super::execute_actions(
         config,
         &config.actions.build,
-        Some(&repo_dir),
+        (!options.no_cd).then_some(repo_dir.as_path()),
+        &repo_dir,
         None,
-        false,
-        false,
+        &super::ExecOptions {
+            log_dir: global_config.step_log_dir(&config.repo.name, "build"),
+            no_fail_fast: options.no_fail_fast || config.actions.keeps_going("build"),
+            spawn_default: config.actions.spawns_by_default("build"),
+            ..options.clone()
+        },
     )
     .await
 }
@@ -0,0 +1,149 @@
+use crate::error::{Error, Result};
+use crate::output::{Marker, marker};
+use anyhow::Context;
+use clap_complete::Shell;
+use std::path::{Path, PathBuf};
+
+const BIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Print the completion script for `shell` to stdout
+pub fn generate_command(shell: Shell, mut cli: clap::Command) -> Result<()> {
+    clap_complete::generate(shell, &mut cli, BIN_NAME, &mut std::io::stdout());
+    Ok(())
+}
+
+/// Write the completion script to the shell's conventional per-user location
+pub fn install_command(shell: Option<Shell>, force: bool, mut cli: clap::Command) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => detect_shell(std::env::var("SHELL").ok().as_deref())?,
+    };
+    let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    let path = completion_path(
+        shell,
+        env("HOME").as_deref(),
+        env("XDG_DATA_HOME").as_deref(),
+        env("XDG_CONFIG_HOME").as_deref(),
+    )?;
+
+    if path.exists() && !force {
+        return Err(Error::Config(format!(
+            "Completion script already exists: {} (use --force to overwrite)",
+            path.display()
+        )));
+    }
+
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cli, BIN_NAME, &mut script);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(&path, script)
+        .with_context(|| format!("Failed to write completion script: {}", path.display()))?;
+
+    println!(
+        "{} Installed {} completions to {}",
+        marker(Marker::Ok),
+        shell,
+        path.display()
+    );
+    println!("{}", activation_hint(shell, &path));
+    Ok(())
+}
+
+/// Shell named by a `$SHELL` path like `/usr/bin/zsh`
+fn detect_shell(shell_path: Option<&str>) -> Result<Shell> {
+    let shell_path = shell_path.filter(|path| !path.is_empty()).ok_or_else(|| {
+        Error::Config("Can't detect the shell: $SHELL is not set; pass --shell".to_string())
+    })?;
+    Shell::from_shell_path(shell_path)
+        .ok_or_else(|| Error::Config(format!("Unsupported shell '{}'; pass --shell", shell_path)))
+}
+
+/// Where `shell` looks for per-user completion scripts
+fn completion_path(
+    shell: Shell,
+    home: Option<&str>,
+    xdg_data: Option<&str>,
+    xdg_config: Option<&str>,
+) -> Result<PathBuf> {
+    let home = || {
+        home.map(PathBuf::from).ok_or_else(|| {
+            Error::Config("Can't locate the home directory: $HOME is not set".to_string())
+        })
+    };
+
+    Ok(match shell {
+        Shell::Bash => xdg_data
+            .map(PathBuf::from)
+            .map_or_else(|| home().map(|home| home.join(".local/share")), Ok)?
+            .join("bash-completion/completions")
+            .join(BIN_NAME),
+        Shell::Zsh => home()?.join(".zfunc").join(format!("_{}", BIN_NAME)),
+        Shell::Fish => xdg_config
+            .map(PathBuf::from)
+            .map_or_else(|| home().map(|home| home.join(".config")), Ok)?
+            .join("fish/completions")
+            .join(format!("{}.fish", BIN_NAME)),
+        other => {
+            return Err(Error::Config(format!(
+                "No conventional completion location for {}; use `completion generate {}` instead",
+                other, other
+            )));
+        }
+    })
+}
+
+/// How to make the shell pick up the installed script
+fn activation_hint(shell: Shell, path: &Path) -> String {
+    match shell {
+        Shell::Bash => format!(
+            "Open a new shell, or run: source {}\n(requires the bash-completion package)",
+            path.display()
+        ),
+        Shell::Zsh => format!(
+            "Add to ~/.zshrc before compinit runs:\n  fpath=({} $fpath)\n  autoload -U compinit && compinit",
+            path.parent().unwrap_or(path).display()
+        ),
+        _ => "Open a new shell to load the completions".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_shell_from_path() {
+        assert_eq!(detect_shell(Some("/usr/bin/zsh")).unwrap(), Shell::Zsh);
+        assert_eq!(detect_shell(Some("/bin/bash")).unwrap(), Shell::Bash);
+        assert!(detect_shell(Some("/bin/tcsh")).is_err());
+        assert!(detect_shell(None).is_err());
+    }
+
+    #[test]
+    fn test_completion_path_per_shell() {
+        let path = |shell, xdg_data, xdg_config| {
+            completion_path(shell, Some("/home/user"), xdg_data, xdg_config).unwrap()
+        };
+
+        assert_eq!(
+            path(Shell::Bash, None, None),
+            Path::new("/home/user/.local/share/bash-completion/completions").join(BIN_NAME)
+        );
+        assert_eq!(
+            path(Shell::Bash, Some("/data"), None),
+            Path::new("/data/bash-completion/completions").join(BIN_NAME)
+        );
+        assert_eq!(
+            path(Shell::Zsh, None, None),
+            Path::new("/home/user/.zfunc").join(format!("_{}", BIN_NAME))
+        );
+        assert_eq!(
+            path(Shell::Fish, None, Some("/config")),
+            Path::new("/config/fish/completions").join(format!("{}.fish", BIN_NAME))
+        );
+        assert!(completion_path(Shell::PowerShell, Some("/home/user"), None, None).is_err());
+    }
+}
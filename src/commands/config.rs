@@ -1,28 +1,96 @@
 use crate::error::Result;
 use crate::global_config::GlobalConfig;
+use crate::output::{Marker, marker};
+use clap::ValueEnum;
 use serde_json;
 
-pub async fn config_command(show: bool, reset: bool, _global_config: &GlobalConfig) -> Result<()> {
+/// Output format for `config --show`
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ConfigFormat {
+    Json,
+    /// Matches the on-disk format
+    #[default]
+    Toml,
+}
+
+pub async fn config_command(
+    show: bool,
+    reset: bool,
+    empty: bool,
+    keep_sources: bool,
+    format: ConfigFormat,
+    _global_config: &GlobalConfig,
+) -> Result<()> {
     let config_path = GlobalConfig::get_config_path();
 
     if reset {
-        let default_config = GlobalConfig::default();
+        let mut default_config = GlobalConfig::default();
+        if empty {
+            default_config.manifest_sources.clear();
+        } else if keep_sources {
+            default_config.manifest_sources = GlobalConfig::load()?.manifest_sources;
+        }
         default_config.save()?;
-        println!("✅ App configuration reset to defaults");
+        if empty {
+            println!(
+                "{} App configuration reset to defaults without manifest sources",
+                marker(Marker::Ok)
+            );
+        } else if keep_sources {
+            println!(
+                "{} App configuration reset to defaults, keeping manifest sources",
+                marker(Marker::Ok)
+            );
+        } else {
+            println!("{} App configuration reset to defaults", marker(Marker::Ok));
+        }
     } else if show {
         let global_config = GlobalConfig::load()?;
-        let config_json = serde_json::to_string_pretty(&global_config).map_err(|e| crate::error::Error::JsonDe(e))?;
         println!("Current app configuration:");
-        println!("{}", config_json);
+        println!("{}", render_config(&global_config, format)?);
         println!("location: {}", config_path.display())
     } else {
         println!("App configuration file: {}", config_path.display());
         if !config_path.exists() {
             let global_config = GlobalConfig::load()?;
             global_config.save()?;
-            println!("✅ Created default app configuration");
+            println!("{} Created default app configuration", marker(Marker::Ok));
         }
     }
 
     Ok(())
 }
+
+fn render_config(global_config: &GlobalConfig, format: ConfigFormat) -> Result<String> {
+    Ok(match format {
+        ConfigFormat::Json => {
+            serde_json::to_string_pretty(global_config).map_err(crate::error::Error::JsonDe)?
+        }
+        ConfigFormat::Toml => toml::to_string_pretty(global_config)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_config_toml_round_trips() {
+        let config = GlobalConfig::default();
+        let rendered = render_config(&config, ConfigFormat::Toml).unwrap();
+
+        let parsed: GlobalConfig = toml::from_str(&rendered).unwrap();
+        assert_eq!(parsed.tools_dir, config.tools_dir);
+        assert_eq!(parsed.manifest_sources.len(), config.manifest_sources.len());
+    }
+
+    #[test]
+    fn test_render_config_json_round_trips() {
+        let config = GlobalConfig::default();
+        let rendered = render_config(&config, ConfigFormat::Json).unwrap();
+
+        let parsed: GlobalConfig = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed.tools_dir, config.tools_dir);
+        assert_eq!(parsed.default_manifest_dir, config.default_manifest_dir);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::error::Result;
+use crate::history;
+use std::time::SystemTime;
+
+pub async fn history_command(tool: Option<&str>, since: Option<&str>) -> Result<()> {
+    let now = history::unix_seconds(SystemTime::now());
+    let cutoff = match since {
+        Some(since) => Some(now.saturating_sub(history::parse_duration(since)?.as_secs())),
+        None => None,
+    };
+
+    let entries = history::load(&history::history_path())?;
+    let entries = history::filter(&entries, tool, cutoff);
+    if entries.is_empty() {
+        println!("No history entries found.");
+        return Ok(());
+    }
+
+    let tool_width = entries
+        .iter()
+        .map(|entry| entry.tool.len())
+        .max()
+        .unwrap_or(0)
+        .max("TOOL".len());
+    println!(
+        "{:<tool_width$}  {:<7}  {:<6}  {:>10}  {:>9}",
+        "TOOL", "ACTION", "STATUS", "WHEN", "DURATION"
+    );
+    for entry in entries {
+        println!(
+            "{:<tool_width$}  {:<7}  {:<6}  {:>10}  {:>7}ms",
+            entry.tool,
+            entry.action,
+            entry.status,
+            history::format_age(entry.timestamp, now),
+            entry.duration_ms
+        );
+    }
+
+    Ok(())
+}
@@ -1,38 +1,390 @@
 use crate::config::Config;
 use crate::error::Result;
 use crate::global_config::GlobalConfig;
+use crate::output::{Marker, ProgressLine, Sink, marker};
 use anyhow::Context;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// What to do with a tool's existing directory before installing
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ExistingInstall {
+    /// Install into the existing directory
+    #[default]
+    Reuse,
+    /// Delete it and install from scratch
+    Remove,
+    /// Move it to a timestamped backup and install from scratch
+    Backup,
+}
+
+/// Install several tools, returning each one's duration and result in input order.
+///
+/// With `progress` the installs run concurrently (up to `max_concurrency` at a time)
+/// under one progress line per tool showing its current step; otherwise they run one
+/// after another with the usual line-by-line output.
+pub async fn install_many_command(
+    configs: &[Config],
+    global_config: &GlobalConfig,
+    existing: ExistingInstall,
+    options: &super::ExecOptions,
+    progress: bool,
+) -> Vec<(Duration, Result<()>)> {
+    if !progress {
+        let mut outcomes = Vec::new();
+        for config in configs {
+            let started = Instant::now();
+            let result = install_command(config, global_config, existing, options).await;
+            if result.is_ok() {
+                options.output.line(&format!(
+                    "{} Installation of {} completed successfully!",
+                    marker(Marker::Ok),
+                    config.repo.name
+                ));
+            }
+            outcomes.push((started.elapsed(), result));
+        }
+        return outcomes;
+    }
+
+    let multi = MultiProgress::new();
+    let style = ProgressStyle::with_template("{spinner} {prefix:.bold} {wide_msg}")
+        .expect("progress template is valid");
+    let installs = configs
+        .iter()
+        .map(|config| {
+            let bar = multi.add(
+                ProgressBar::new_spinner()
+                    .with_style(style.clone())
+                    .with_prefix(config.repo.name.clone())
+                    .with_message("waiting"),
+            );
+            bar.enable_steady_tick(Duration::from_millis(100));
+            (config, bar)
+        })
+        .collect();
+
+    super::run_bounded(installs, global_config.max_concurrency, |(config, bar)| {
+        let options = super::ExecOptions {
+            output: Sink::new(Arc::new(ProgressLine(bar.clone()))),
+            ..options.clone()
+        };
+        async move {
+            let started = Instant::now();
+            let result = install_command(config, global_config, existing, &options).await;
+            bar.finish_with_message(match &result {
+                Ok(()) => format!("{} installed", marker(Marker::Ok)),
+                Err(_) => format!("{} failed", marker(Marker::Fail)),
+            });
+            (started.elapsed(), result)
+        }
+    })
+    .await
+}
 
 pub async fn install_command(
     config: &Config,
     global_config: &GlobalConfig,
+    existing: ExistingInstall,
+    options: &super::ExecOptions,
+) -> Result<()> {
+    install_tool(config, global_config, existing, options).await?;
+    if options.verify {
+        super::verify::verify_command(config, global_config, options).await?;
+    }
+    Ok(())
+}
+
+async fn install_tool(
+    config: &Config,
+    global_config: &GlobalConfig,
+    existing: ExistingInstall,
+    options: &super::ExecOptions,
 ) -> Result<()> {
-    println!("Installing {}...", config.repo.name);
+    options
+        .output
+        .line(&format!("Installing {}...", config.repo.name));
 
     // Check if repo directory already exists in the tools directory
     let tools_dir = global_config.get_tools_directory();
-    let repo_dir = tools_dir.join(&config.repo.name);
+    let repo_dir = config.repo_dir(&tools_dir)?;
+
+    if repo_dir.exists() {
+        match existing {
+            ExistingInstall::Reuse => {}
+            ExistingInstall::Remove => {
+                std::fs::remove_dir_all(&repo_dir).with_context(|| {
+                    format!("Failed to remove repo directory: {}", repo_dir.display())
+                })?;
+                options
+                    .output
+                    .line(&format!("Removed existing {}", repo_dir.display()));
+            }
+            ExistingInstall::Backup => {
+                let backup = backup_path(
+                    &repo_dir,
+                    crate::history::unix_seconds(std::time::SystemTime::now()),
+                );
+                std::fs::rename(&repo_dir, &backup).with_context(|| {
+                    format!("Failed to back up repo directory: {}", repo_dir.display())
+                })?;
+                options.output.line(&format!(
+                    "{} Moved existing install to {}",
+                    marker(Marker::Ok),
+                    backup.display()
+                ));
+            }
+        }
+    }
 
     if !repo_dir.exists() {
-        std::fs::create_dir_all(&repo_dir).with_context(|| {
-            format!("Failed to create repo directory: {}", repo_dir.display())
-        })?;
+        std::fs::create_dir_all(&repo_dir)
+            .with_context(|| format!("Failed to create repo directory: {}", repo_dir.display()))?;
     }
 
     if repo_dir.exists() {
-        println!(
+        options.output.line(&format!(
             "Repository {} already exists. Proceeding with installation actions.",
             config.repo.name
-        );
+        ));
+    }
+
+    super::ensure_dependencies(config, options).await?;
+    if config.actions.installation.is_empty() && !config.dependencies.is_empty() {
+        options
+            .output
+            .line("No installation actions; installation consisted only of dependency setup");
+        return Ok(());
     }
 
     super::execute_actions(
         config,
         &config.actions.installation,
-        Some(tools_dir),
+        Some(&tools_dir),
+        &repo_dir,
         None,
-        false,
-        false,
+        &super::ExecOptions {
+            log_dir: global_config.step_log_dir(&config.repo.name, "install"),
+            no_fail_fast: options.no_fail_fast || config.actions.keeps_going("installation"),
+            spawn_default: config.actions.spawns_by_default("installation"),
+            ..options.clone()
+        },
     )
+    .await?;
+
+    // The walk can be slow on large trees, so keep it off the async workers; the
+    // install already succeeded, so a failed walk only warns
+    let hash_requested = options.content_hash;
+    let (footprint, hash) = tokio::task::spawn_blocking(move || {
+        let footprint = footprint(&repo_dir);
+        let hash = hash_requested.then(|| content_hash(&repo_dir));
+        (footprint, hash)
+    })
     .await
+    .context("Failed to summarize the install directory")?;
+
+    match footprint {
+        Ok(footprint) => options.output.line(&format!(
+            "Install size: {} in {} files",
+            format_size(footprint.bytes),
+            footprint.files
+        )),
+        Err(e) => options.output.diagnostic(&format!(
+            "{} Could not measure the install size: {}",
+            marker(Marker::Warn),
+            e
+        )),
+    }
+    match hash {
+        Some(Ok(hash)) => options
+            .output
+            .line(&format!("Content hash: sha256:{}", hash)),
+        Some(Err(e)) => options.output.diagnostic(&format!(
+            "{} Could not hash the install directory: {}",
+            marker(Marker::Warn),
+            e
+        )),
+        None => {}
+    }
+    Ok(())
+}
+
+/// Disk usage of an install directory
+#[derive(Debug, Default, PartialEq)]
+struct Footprint {
+    bytes: u64,
+    files: u64,
+}
+
+/// Total size and number of files under `dir`, without following symlinks
+fn footprint(dir: &Path) -> Result<Footprint> {
+    let mut total = Footprint::default();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+    {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let nested = footprint(&entry.path())?;
+            total.bytes += nested.bytes;
+            total.files += nested.files;
+        } else {
+            total.bytes += entry.metadata()?.len();
+            total.files += 1;
+        }
+    }
+    Ok(total)
+}
+
+/// Hex SHA-256 over `dir`'s tree: each directory hashes its entries in name order, each
+/// entry contributing its name, kind and the hash of its content (or of its link target),
+/// so any rename, edit or added file changes the result
+fn content_hash(dir: &Path) -> Result<String> {
+    let mut entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut hasher = Sha256::new();
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        let (kind, digest) = if file_type.is_dir() {
+            ("dir", content_hash(&path)?)
+        } else if file_type.is_symlink() {
+            let target = std::fs::read_link(&path)?;
+            (
+                "link",
+                hex(&Sha256::digest(target.as_os_str().as_encoded_bytes())),
+            )
+        } else {
+            let mut file = std::fs::File::open(&path)
+                .with_context(|| format!("Failed to read file: {}", path.display()))?;
+            let mut file_hasher = Sha256::new();
+            std::io::copy(&mut file, &mut file_hasher)
+                .with_context(|| format!("Failed to read file: {}", path.display()))?;
+            ("file", hex(&file_hasher.finalize()))
+        };
+        hasher.update(entry.file_name().as_encoded_bytes());
+        hasher.update([0]);
+        hasher.update(kind);
+        hasher.update([0]);
+        hasher.update(digest);
+        hasher.update([b'\n']);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Byte count in the largest binary unit that keeps it at or above 1, e.g. "1.5 MiB"
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// `<dir>.bak-<timestamp>` next to `dir`
+fn backup_path(dir: &Path, timestamp: u64) -> PathBuf {
+    let mut name = dir.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak-{}", timestamp));
+    dir.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_path_sits_next_to_install() {
+        assert_eq!(
+            backup_path(Path::new("/tools/fd"), 1700000000),
+            PathBuf::from("/tools/fd.bak-1700000000")
+        );
+    }
+
+    #[test]
+    fn test_format_size_units() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn test_footprint_and_content_hash_track_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::create_dir(dir.join("bin")).unwrap();
+        std::fs::write(dir.join("README"), "hello").unwrap();
+        std::fs::write(dir.join("bin/tool"), "#!/bin/sh\n").unwrap();
+
+        assert_eq!(
+            footprint(dir).unwrap(),
+            Footprint {
+                bytes: 15,
+                files: 2
+            }
+        );
+        let hash = content_hash(dir).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(content_hash(dir).unwrap(), hash);
+
+        std::fs::rename(dir.join("README"), dir.join("README.md")).unwrap();
+        assert_ne!(content_hash(dir).unwrap(), hash);
+    }
+
+    #[tokio::test]
+    async fn test_install_messages_go_to_output_sink() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config: Config = serde_json::from_str(
+            r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "prepare", "command": "true" }],
+    "update": [], "build": [], "run": []
+  }
+}"#,
+        )
+        .unwrap();
+        let global_config = GlobalConfig {
+            tools_dir: temp_dir.path().join("tools"),
+            ..Default::default()
+        };
+        let captured = std::sync::Arc::new(crate::output::Captured::default());
+        let options = super::super::ExecOptions {
+            output: crate::output::Sink::new(captured.clone()),
+            ..Default::default()
+        };
+
+        install_command(&config, &global_config, ExistingInstall::Reuse, &options)
+            .await
+            .unwrap();
+
+        let lines = captured.lines();
+        assert_eq!(lines[0], "Installing tool...");
+        assert!(lines.contains(&"[1/1] Step 1: prepare".to_string()));
+        assert!(lines.contains(&"Executing: true".to_string()));
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.ends_with("Completed: prepare\n"))
+        );
+        assert!(captured.diagnostics().is_empty());
+    }
 }
@@ -0,0 +1,45 @@
+use crate::config::{Actions, Config};
+use crate::error::Result;
+use crate::global_config::GlobalConfig;
+use crate::output::{Marker, marker};
+use anyhow::Context;
+
+/// Print the numbered, interpolated steps of `phase` in the order they would run,
+/// marking the ones this OS skips
+pub fn plan_command(config: &Config, phase: &str, global_config: &GlobalConfig) -> Result<()> {
+    let tools_dir = global_config.get_tools_directory();
+    let install_dir = std::path::absolute(config.repo_dir(&tools_dir)?)
+        .context("Failed to resolve the tool directory")?;
+    let resolved = config.resolved(&install_dir, &global_config.workspace_dir()?);
+    let actions = resolved.actions.phase(phase).ok_or_else(|| {
+        crate::error::Error::Config(format!(
+            "Unknown phase '{}': expected one of {}",
+            phase,
+            Actions::PHASES.join(", ")
+        ))
+    })?;
+
+    println!("Plan for {} ({}):", config.repo.name, phase);
+    if actions.is_empty() {
+        println!("  (no steps)");
+    }
+    for (index, action) in actions.iter().enumerate() {
+        let step = format!(
+            "  {}. Step {}: {}",
+            index + 1,
+            action.seq_id,
+            action.description
+        );
+        if action.runs_on(std::env::consts::OS) {
+            println!("{}: {}", step, action.invocation()?);
+        } else {
+            println!(
+                "{} {} only runs on {}",
+                step,
+                marker(Marker::Skip),
+                action.os.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,34 +1,205 @@
+use super::ExecOptions;
 use crate::config::Config;
 use crate::error::Result;
 use crate::global_config::GlobalConfig;
+use crate::output::{Marker, marker};
+use anyhow::Context;
+use notify::{RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
 
 pub async fn run_command(
     config: &Config,
     args: &[String],
-    force_spawn: bool,
-    force_wait: bool,
+    options: &ExecOptions,
     global_config: &GlobalConfig,
 ) -> Result<()> {
-    println!("Running {}...", config.repo.name);
+    options
+        .output
+        .line(&format!("Running {}...", config.repo.name));
 
     let tools_dir = global_config.get_tools_directory();
-    let repo_dir = tools_dir.join(&config.repo.name);
+    let repo_dir = config.repo_dir(&tools_dir)?;
 
     if !repo_dir.exists() {
-        println!(
+        options.output.line(&format!(
             "Repository {} does not exist. Use 'install' command first.",
             config.repo.name
-        );
+        ));
         return Ok(());
     }
 
+    if options.with_deps {
+        super::ensure_dependencies(config, options).await?;
+    }
+
+    // Detached runs always log, under the cache directory when output_dir isn't set
+    let log_dir = global_config
+        .step_log_dir(&config.repo.name, "run")
+        .or_else(|| {
+            options
+                .detach
+                .then(|| GlobalConfig::detached_log_dir(&config.repo.name))
+        });
+    let options = ExecOptions {
+        log_dir,
+        no_fail_fast: options.no_fail_fast || config.actions.keeps_going("run"),
+        spawn_default: config.actions.spawns_by_default("run"),
+        ..options.clone()
+    };
     super::execute_actions(
         config,
         &config.actions.run,
-        Some(&repo_dir),
+        (!options.no_cd).then_some(repo_dir.as_path()),
+        &repo_dir,
         Some(args),
-        force_spawn,
-        force_wait,
+        &options,
     )
     .await
 }
+
+/// Quiet period after a change before the run restarts, so bursts of events trigger one run
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Run the tool, restarting the run whenever something under `watch_path` changes.
+///
+/// Each run gets a child of `options.cancel`: a change arriving mid-run cancels it (its
+/// waited-on process is stopped and the remaining steps skipped) before the next run
+/// starts. Returns once `options.cancel` fires, e.g. on Ctrl-C.
+pub async fn watch_command(
+    config: &Config,
+    args: &[String],
+    options: &ExecOptions,
+    global_config: &GlobalConfig,
+    watch_path: &Path,
+) -> Result<()> {
+    let (tx, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event
+            && !event.kind.is_access()
+        {
+            let _ = tx.send(());
+        }
+    })
+    .context("Failed to create file watcher")?;
+    watcher
+        .watch(watch_path, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", watch_path.display()))?;
+
+    options.output.line(&format!(
+        "Watching {} for changes (Ctrl-C to stop)",
+        watch_path.display()
+    ));
+
+    loop {
+        let run_options = ExecOptions {
+            cancel: options.cancel.child_token(),
+            ..options.clone()
+        };
+        let run = run_command(config, args, &run_options, global_config);
+        tokio::pin!(run);
+
+        let (finished, watching) = tokio::select! {
+            result = &mut run => (Some(result), true),
+            changed = changes.recv() => {
+                // Let the run stop its process before starting over
+                run_options.cancel.cancel();
+                let _ = run.await;
+                (None, changed.is_some())
+            }
+        };
+        if options.cancel.is_cancelled() {
+            return Err(crate::error::Error::Cancelled);
+        }
+        if !watching {
+            return Ok(());
+        }
+
+        match finished {
+            Some(result) => {
+                match result {
+                    Ok(()) => options.output.line(&format!(
+                        "{} Run finished; waiting for changes",
+                        marker(Marker::Done)
+                    )),
+                    Err(e) => options.output.diagnostic(&format!(
+                        "{} Run failed: {}",
+                        marker(Marker::Fail),
+                        e
+                    )),
+                }
+                tokio::select! {
+                    changed = changes.recv() => {
+                        if changed.is_none() {
+                            return Ok(());
+                        }
+                    }
+                    _ = options.cancel.cancelled() => return Err(crate::error::Error::Cancelled),
+                }
+            }
+            None => options.output.line("Change detected; restarting the run"),
+        }
+
+        // Swallow the rest of a burst of events before running again
+        while let Ok(Some(())) = tokio::time::timeout(WATCH_DEBOUNCE, changes.recv()).await {}
+    }
+}
+
+/// Print, sorted, the environment the run actions would receive: the inherited
+/// environment, then the manifest's `env_file`, then `--env` values
+pub fn print_env_command(
+    config: &Config,
+    options: &ExecOptions,
+    global_config: &GlobalConfig,
+) -> Result<()> {
+    let tools_dir = global_config.get_tools_directory();
+    let repo_dir = std::path::absolute(config.repo_dir(&tools_dir)?)
+        .context("Failed to resolve the tool directory")?;
+
+    let mut env: BTreeMap<String, String> = std::env::vars().collect();
+    env.extend(super::load_env_file(config, &repo_dir, &*options.output)?);
+    env.extend(options.env.iter().cloned());
+
+    for (key, value) in env {
+        options.output.line(&format!("{}={}", key, value));
+    }
+    Ok(())
+}
+
+/// Parse `--env` values of the form `KEY=VALUE`
+pub fn parse_env_vars(assignments: &[String]) -> Result<Vec<(String, String)>> {
+    assignments
+        .iter()
+        .map(|assignment| match assignment.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+            _ => Err(crate::error::Error::Config(format!(
+                "Invalid --env '{}': expected KEY=VALUE",
+                assignment
+            ))),
+        })
+        .collect()
+}
+
+/// Read whitespace-separated arguments from a file, skipping `#` comment lines
+pub fn read_args_file(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read args file: {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .flat_map(str::split_whitespace)
+        .map(String::from)
+        .collect())
+}
+
+/// Keep only the arguments matching `pattern`
+pub fn filter_args(args: Vec<String>, pattern: &str) -> Result<Vec<String>> {
+    let regex = regex::Regex::new(pattern).map_err(|e| {
+        crate::error::Error::Config(format!("Invalid --filter pattern '{}': {}", pattern, e))
+    })?;
+
+    Ok(args.into_iter().filter(|arg| regex.is_match(arg)).collect())
+}
@@ -2,17 +2,23 @@ use crate::config::Config;
 use crate::error::Result;
 use crate::global_config::GlobalConfig;
 
-pub async fn update_command(config: &Config, global_config: &GlobalConfig) -> Result<()> {
-    println!("Updating {}...", config.repo.name);
+pub async fn update_command(
+    config: &Config,
+    global_config: &GlobalConfig,
+    options: &super::ExecOptions,
+) -> Result<()> {
+    options
+        .output
+        .line(&format!("Updating {}...", config.repo.name));
 
     let tools_dir = global_config.get_tools_directory();
-    let repo_dir = tools_dir.join(&config.repo.name);
+    let repo_dir = config.repo_dir(&tools_dir)?;
 
     if !repo_dir.exists() {
-        println!(
+        options.output.line(&format!(
             "Repository {} does not exist. Use 'install' command first.",
             config.repo.name
-        );
+        ));
         return Ok(());
     }
 
@@ -20,9 +26,14 @@ pub async fn update_command(config: &Config, global_config: &GlobalConfig) -> Re
         config,
         &config.actions.update,
         Some(&repo_dir),
+        &repo_dir,
         None,
-        false,
-        false,
+        &super::ExecOptions {
+            log_dir: global_config.step_log_dir(&config.repo.name, "update"),
+            no_fail_fast: options.no_fail_fast || config.actions.keeps_going("update"),
+            spawn_default: config.actions.spawns_by_default("update"),
+            ..options.clone()
+        },
     )
     .await
 }
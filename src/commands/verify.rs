@@ -0,0 +1,47 @@
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::global_config::GlobalConfig;
+
+/// Run the manifest's verify actions in the tool's directory; they always wait,
+/// so a non-zero exit fails verification
+pub async fn verify_command(
+    config: &Config,
+    global_config: &GlobalConfig,
+    options: &super::ExecOptions,
+) -> Result<()> {
+    options
+        .output
+        .line(&format!("Verifying {}...", config.repo.name));
+
+    let tools_dir = global_config.get_tools_directory();
+    let repo_dir = config.repo_dir(&tools_dir)?;
+
+    if !repo_dir.exists() {
+        return Err(Error::NotFound(format!(
+            "{} is not installed; use 'install' first",
+            config.repo.name
+        )));
+    }
+    if config.actions.verify.is_empty() {
+        options.output.line(&format!(
+            "No verify actions for {}; nothing to check",
+            config.repo.name
+        ));
+        return Ok(());
+    }
+
+    super::execute_actions(
+        config,
+        &config.actions.verify,
+        Some(&repo_dir),
+        &repo_dir,
+        None,
+        &super::ExecOptions {
+            log_dir: global_config.step_log_dir(&config.repo.name, "verify"),
+            no_fail_fast: options.no_fail_fast || config.actions.keeps_going("verify"),
+            force_wait: true,
+            ..options.clone()
+        },
+    )
+    .await
+}
@@ -0,0 +1,22 @@
+use crate::error::Result;
+use crate::global_config::GlobalConfig;
+
+pub async fn version_command(global_config: &GlobalConfig) -> Result<()> {
+    let defaults = GlobalConfig::embedded_defaults();
+
+    println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    println!("config file: {}", GlobalConfig::get_config_path().display());
+    println!(
+        "tools_dir: {}",
+        global_config.get_tools_directory().display()
+    );
+    println!("cache dir: {}", GlobalConfig::get_cache_dir().display());
+    println!("embedded defaults:");
+    println!("  config_file_name: {}", defaults.config_file_name);
+    println!("  app_config_path: {}", defaults.app_config_path.join(", "));
+    println!("  manifests_dir: {}", defaults.manifests_dir);
+    println!("  tools_dir: {}", defaults.tools_dir.join(", "));
+    println!("  tools_sources_path: {}", defaults.tools_sources_path);
+
+    Ok(())
+}
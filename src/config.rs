@@ -1,101 +1,1664 @@
 use crate::error::Result;
+use crate::output::{Marker, marker};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Parsed manifests keyed by path, reused while the file's mtime is unchanged
+static CONFIG_CACHE: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, Config)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    /// JSON schema reference used by editors; ignored by the app
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Free-form comment; ignored by the app
+    #[serde(rename = "_comment", default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Oldest tool-meister version that understands this manifest (e.g. "0.3.0")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
     pub repo: Repository,
     pub dependencies: Vec<Dependency>,
     pub actions: Actions,
     /// Arguments that should trigger wait-and-show-output behavior (instead of spawning)
     #[serde(default)]
     pub info_args: Vec<String>,
+    /// Also treat arguments that start with an info arg (e.g. `--help=json`) as informational
+    #[serde(default)]
+    pub info_args_prefix: bool,
+    /// Compare arguments against info args ignoring case (e.g. `--Help` matches `--help`)
+    #[serde(default)]
+    pub case_insensitive_info_args: bool,
+    /// File of `KEY=VALUE` lines, relative to the tool's directory, added to every action's environment
+    #[serde(default)]
+    pub env_file: Option<String>,
+    /// Fail instead of warning when `env_file` doesn't exist
+    #[serde(default)]
+    pub env_file_required: bool,
+    /// Directory of the manifest file, which relative `script` paths resolve against
+    #[serde(skip)]
+    pub manifest_dir: Option<PathBuf>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Repository {
     pub name: String,
     pub url: String,
     pub default_branch: Branch,
+    /// Directory name under tools_dir to use instead of `name`
+    #[serde(default)]
+    pub install_dir: Option<String>,
+    /// Commit or tag to install instead of the tip of `default_branch`
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Branch {
     pub name: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Dependency {
     pub name: String,
     pub version: String,
     pub url: String,
+    /// Command that exits successfully when the dependency is present
+    #[serde(default)]
+    pub check: Option<String>,
+    /// Command that installs the dependency when `check` fails
+    #[serde(default)]
+    pub install: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Actions {
     pub installation: Vec<Action>,
     pub update: Vec<Action>,
     pub build: Vec<Action>,
     pub run: Vec<Action>,
+    /// Checks that an installed tool works, e.g. `tool --version`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verify: Vec<Action>,
+    /// Phases that attempt every action even after one fails (e.g. `["build"]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_fail_fast: Vec<String>,
+    /// Phases whose actions spawn unless they set `spawn` themselves (e.g. `["run"]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spawn_default: Vec<String>,
+}
+
+impl Actions {
+    pub const PHASES: [&str; 5] = ["installation", "update", "build", "run", "verify"];
+
+    /// The actions of a phase named in `PHASES`
+    pub fn phase(&self, phase: &str) -> Option<&[Action]> {
+        match phase {
+            "installation" => Some(&self.installation),
+            "update" => Some(&self.update),
+            "build" => Some(&self.build),
+            "run" => Some(&self.run),
+            "verify" => Some(&self.verify),
+            _ => None,
+        }
+    }
+
+    /// Each phase's name and actions, in `PHASES` order
+    pub fn iter_phases(&self) -> impl Iterator<Item = (&'static str, &[Action])> {
+        Self::PHASES.into_iter().map(|name| {
+            (
+                name,
+                self.phase(name).expect("every phase in PHASES is known"),
+            )
+        })
+    }
+
+    /// Every action of every phase, in `PHASES` order
+    pub fn all(&self) -> impl Iterator<Item = &Action> {
+        self.iter_phases().flat_map(|(_, actions)| actions)
+    }
+
+    /// Every action of every phase, in `PHASES` order, for editing in place
+    fn all_mut(&mut self) -> impl Iterator<Item = &mut Action> {
+        [
+            &mut self.installation,
+            &mut self.update,
+            &mut self.build,
+            &mut self.run,
+            &mut self.verify,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Whether `phase` keeps going past failing actions
+    pub fn keeps_going(&self, phase: &str) -> bool {
+        self.no_fail_fast.iter().any(|name| name == phase)
+    }
+
+    /// Whether actions of `phase` spawn when they don't set `spawn`
+    pub fn spawns_by_default(&self, phase: &str) -> bool {
+        self.spawn_default.iter().any(|name| name == phase)
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Action {
-    #[serde(rename = "seq-id")]
+    #[serde(rename = "seq-id", deserialize_with = "deserialize_seq_id")]
     pub seq_id: u32,
     pub name: Option<String>,
-    pub command: String,
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Script file run through the shell instead of `command` (relative to the manifest's directory)
+    #[serde(default)]
+    pub script: Option<String>,
     pub description: String,
+    /// Detach instead of waiting; unset follows the phase's `spawn_default`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawn: Option<bool>,
+    /// Shell used instead of `sh -c` (e.g. "bash" or "python3 -c")
     #[serde(default)]
-    pub spawn: bool,
+    pub shell: Option<String>,
+    /// Attach the terminal instead of capturing output, for commands that prompt
+    #[serde(default)]
+    pub interactive: bool,
+    /// Kill the command if it hasn't finished after this many seconds
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Operating systems the action runs on (as in `std::env::consts::OS`, e.g. "linux",
+    /// "macos"); empty means every OS
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub os: Vec<String>,
+    /// Built-in action type; actions without a `type` are shell actions
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ActionKind>,
+    /// Repository a `git_clone` action clones instead of the manifest's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Branch or tag a `git_clone` action clones instead of the manifest's pinned `ref`
+    /// (or default branch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+/// Largest `seq-id` accepted without a warning; bigger values usually mean a generated
+/// or mistyped manifest
+const MAX_SEQ_ID: u32 = 1_000_000;
+
+/// Read a `seq-id`, naming the field and the accepted range when the value doesn't fit
+fn deserialize_seq_id<'de, D>(deserializer: D) -> std::result::Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct SeqId;
+
+    impl serde::de::Visitor<'_> for SeqId {
+        type Value = u32;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "seq-id to be a whole number from 0 to {}", u32::MAX)
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, value: u64) -> std::result::Result<u32, E> {
+            u32::try_from(value).map_err(|_| {
+                E::custom(format!(
+                    "seq-id {} is too large: expected at most {}",
+                    value,
+                    u32::MAX
+                ))
+            })
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, value: i64) -> std::result::Result<u32, E> {
+            match u64::try_from(value) {
+                Ok(value) => self.visit_u64(value),
+                Err(_) => Err(E::custom(format!(
+                    "seq-id {} is negative: expected a whole number from 0 to {}",
+                    value,
+                    u32::MAX
+                ))),
+            }
+        }
+    }
+
+    deserializer.deserialize_u32(SeqId)
+}
+
+/// Action types selected by an action's `type` field
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    /// Run `command` or `script` through the shell
+    Shell,
+    /// Clone a repository, defaulting to the manifest's repo url and default branch
+    GitClone,
+    /// Pull the repository in the working directory
+    GitPull,
+}
+
+/// What an action executes: an inline command, a script file or a built-in git operation
+#[derive(Debug, PartialEq)]
+pub enum ActionBody<'a> {
+    Command(&'a str),
+    Script(&'a str),
+    GitClone {
+        url: Option<&'a str>,
+        branch: Option<&'a str>,
+    },
+    GitPull,
+}
+
+impl Action {
+    /// Whether the action is spawn-capable, given its phase's `spawn_default`.
+    /// The phase default never applies to interactive or timed actions, which must be waited on.
+    pub fn spawns(&self, phase_default: bool) -> bool {
+        self.spawn
+            .unwrap_or(phase_default && !self.interactive && self.timeout_secs.is_none())
+    }
+
+    /// Whether the action applies on `os`
+    pub fn runs_on(&self, os: &str) -> bool {
+        self.os.is_empty() || self.os.iter().any(|name| name == os)
+    }
+
+    /// One-line description of what the action runs, as shown by `manifests show` and `plan`
+    pub fn invocation(&self) -> Result<String> {
+        Ok(match self.body()? {
+            ActionBody::Command(command) => command.to_string(),
+            ActionBody::Script(script) => format!("script {}", script),
+            ActionBody::GitClone { url, branch } => format!(
+                "git clone {}{}",
+                branch
+                    .map(|b| format!("--branch {} ", b))
+                    .unwrap_or_default(),
+                url.unwrap_or("[[repo.url]]")
+            ),
+            ActionBody::GitPull => "git pull".to_string(),
+        })
+    }
+
+    /// What the action runs; shell actions require exactly one of command and script,
+    /// built-in types take neither, and only `git_clone` takes url and branch
+    pub fn body(&self) -> Result<ActionBody<'_>> {
+        if self.kind != Some(ActionKind::GitClone) && (self.url.is_some() || self.branch.is_some())
+        {
+            return Err(crate::error::Error::Config(format!(
+                "Step {} ({}) sets url or branch, which only git_clone actions take",
+                self.seq_id, self.description
+            )));
+        }
+
+        let builtin = match self.kind {
+            None | Some(ActionKind::Shell) => None,
+            Some(ActionKind::GitClone) => Some(ActionBody::GitClone {
+                url: self.url.as_deref(),
+                branch: self.branch.as_deref(),
+            }),
+            Some(ActionKind::GitPull) => Some(ActionBody::GitPull),
+        };
+        if let Some(body) = builtin {
+            if self.command.is_some() || self.script.is_some() {
+                return Err(crate::error::Error::Config(format!(
+                    "Step {} ({}) is a built-in git action and can't set command or script",
+                    self.seq_id, self.description
+                )));
+            }
+            return Ok(body);
+        }
+
+        match (&self.command, &self.script) {
+            (Some(command), None) => Ok(ActionBody::Command(command)),
+            (None, Some(script)) => Ok(ActionBody::Script(script)),
+            (Some(_), Some(_)) => Err(crate::error::Error::Config(format!(
+                "Step {} ({}) sets both command and script",
+                self.seq_id, self.description
+            ))),
+            (None, None) => Err(crate::error::Error::Config(format!(
+                "Step {} ({}) needs a command or a script",
+                self.seq_id, self.description
+            ))),
+        }
+    }
 }
 
 impl Config {
-    pub fn load(config_dir: &Path, tool_name: &str) -> Result<Self> {
-        let config_path = config_dir.join(format!("{}.jsonc", tool_name));
-        Self::load_from_path(&config_path)
+    pub fn load(config_dir: &Path, tool_name: &str, formats: &[String]) -> Result<Self> {
+        validate_tool_name(tool_name)?;
+        Self::load_from_path(&Self::manifest_path(config_dir, tool_name, formats))
+    }
+
+    /// Path of a tool's manifest in `dir`, probing the extensions in `formats` in order.
+    ///
+    /// A `<tool>.local.<ext>` override wins over any `<tool>.<ext>`. When nothing exists
+    /// the path for the first format is returned.
+    pub fn manifest_path(dir: &Path, tool_name: &str, formats: &[String]) -> PathBuf {
+        let candidates = |suffix: &'static str| {
+            formats
+                .iter()
+                .map(move |ext| dir.join(format!("{}{}.{}", tool_name, suffix, ext)))
+        };
+
+        candidates(".local")
+            .chain(candidates(""))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| {
+                let ext = formats.first().map_or("jsonc", String::as_str);
+                dir.join(format!("{}.{}", tool_name, ext))
+            })
     }
 
     pub fn load_from_path(config_path: &Path) -> Result<Self> {
+        let modified = std::fs::metadata(config_path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        let key = config_path
+            .canonicalize()
+            .unwrap_or_else(|_| config_path.to_path_buf());
+
+        let mut cache = CONFIG_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_modified, config)) = cache.get(&key)
+            && *cached_modified == modified
+        {
+            return Ok(config.clone());
+        }
+
         let content = std::fs::read_to_string(config_path)
             .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        let config = Self::parse(&content, config_path)?;
+        for warning in config.seq_id_warnings() {
+            eprintln!(
+                "{} {}: {}",
+                marker(Marker::Warn),
+                config_path.display(),
+                warning
+            );
+        }
+        cache.insert(key, (modified, config.clone()));
+        Ok(config)
+    }
+
+    /// Reject the manifest when it requires a newer tool-meister than `current`
+    fn check_min_version(&self, current: &str) -> Result<()> {
+        let Some(required) = &self.min_version else {
+            return Ok(());
+        };
+        let required_version = semver::Version::parse(required).map_err(|e| {
+            crate::error::Error::Config(format!("Invalid min_version '{}': {}", required, e))
+        })?;
+        let current_version =
+            semver::Version::parse(current).context("Invalid tool-meister version")?;
+
+        if current_version < required_version {
+            return Err(crate::error::Error::Config(format!(
+                "Manifest for {} requires tool-meister {} or newer, but this is {}; please upgrade tool-meister",
+                self.repo.name, required, current
+            )));
+        }
+        Ok(())
+    }
 
-        // Remove comments for basic JSONC support
-        let json_content = Self::strip_comments(&content);
+    /// Reject actions whose settings contradict each other
+    fn validate(&self) -> Result<()> {
+        for action in self.actions.all() {
+            // A spawned process is detached: nothing is attached to it and nothing waits on it
+            let spawn = action.spawn == Some(true);
+            let conflict = if spawn && action.interactive {
+                "spawn and interactive"
+            } else if spawn && action.timeout_secs.is_some() {
+                "spawn and timeout_secs"
+            } else {
+                continue;
+            };
+            return Err(crate::error::Error::Config(format!(
+                "Step {} ({}) sets both {}",
+                action.seq_id, action.description, conflict
+            )));
+        }
 
-        let config: Config = serde_json::from_str(&json_content)
-            .with_context(|| format!("Failed to parse config file: {}", config_path.display()))?;
+        // Names select actions, so each may appear only once per phase
+        let actions = &self.actions;
+        for (phase, phase_actions) in actions.iter_phases() {
+            let mut seen = HashSet::new();
+            let names = phase_actions
+                .iter()
+                .filter_map(|action| action.name.as_deref())
+                .filter(|name| !name.trim().is_empty());
+            for name in names {
+                if !seen.insert(name) {
+                    return Err(crate::error::Error::Config(format!(
+                        "Duplicate action name '{}' in the {} phase",
+                        name, phase
+                    )));
+                }
+            }
+        }
+
+        for (field, phases) in [
+            ("no_fail_fast", &actions.no_fail_fast),
+            ("spawn_default", &actions.spawn_default),
+        ] {
+            if let Some(phase) = phases
+                .iter()
+                .find(|phase| !Actions::PHASES.contains(&phase.as_str()))
+            {
+                return Err(crate::error::Error::Config(format!(
+                    "Unknown phase '{}' in {}: expected one of {}",
+                    phase,
+                    field,
+                    Actions::PHASES.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
 
+    /// Steps whose `seq-id` is 0 (reserved) or implausibly large
+    fn seq_id_warnings(&self) -> Vec<String> {
+        self.actions
+            .all()
+            .filter_map(|action| match action.seq_id {
+                0 => Some(format!(
+                    "Step {} ({}) uses seq-id 0, which is reserved; start at 1",
+                    action.seq_id, action.description
+                )),
+                id if id > MAX_SEQ_ID => Some(format!(
+                    "Step {} ({}) has an unusually large seq-id (above {})",
+                    action.seq_id, action.description, MAX_SEQ_ID
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Parse and validate manifest `content` as if it were read from `config_path`,
+    /// whose extension picks the format
+    pub fn parse(content: &str, config_path: &Path) -> Result<Self> {
+        let config = Self::parse_content(content, config_path)?;
+        config.check_min_version(env!("CARGO_PKG_VERSION"))?;
+        config.validate()?;
         Ok(config)
     }
 
-    /// Basic JSONC comment stripping (removes // comments)
+    fn parse_content(content: &str, config_path: &Path) -> Result<Self> {
+        // Manifests written on Windows use CRLF; strip_comments works line by line on `\n`
+        let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+        let parse_error = |message: String| {
+            crate::error::Error::Config(format!(
+                "Failed to parse config file {}: {}",
+                config_path.display(),
+                message
+            ))
+        };
+        match config_path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => {
+                return serde_yaml::from_str(&content).map_err(|e| parse_error(e.to_string()));
+            }
+            Some("toml") => {
+                return toml::from_str(&content).map_err(|e| parse_error(e.to_string()));
+            }
+            _ => {}
+        }
+
+        // Remove comments and trailing commas for JSONC support
+        let json_content = Self::strip_trailing_commas(&Self::strip_comments(&content));
+
+        // Both steps keep every line in place, so serde's position matches the original file
+        serde_json::from_str(&json_content).map_err(|e| {
+            let position = format!(" at line {} column {}", e.line(), e.column());
+            let message = e.to_string();
+            crate::error::Error::Config(format!(
+                "Failed to parse config file {}:{}:{}: {}",
+                config_path.display(),
+                e.line(),
+                e.column(),
+                message.strip_suffix(&position).unwrap_or(&message)
+            ))
+        })
+    }
+
+    /// JSONC comment stripping: removes `//` line comments and `/* */` block comments
+    /// outside string literals. Block comments become spaces and keep their newlines, so
+    /// line and column positions still match the original file.
     fn strip_comments(content: &str) -> String {
-        content
-            .lines()
-            .map(|line| {
-                if let Some(pos) = line.find("//") {
-                    // Check if // is inside quotes
-                    let before_comment = &line[..pos];
-                    let quote_count = before_comment.matches('"').count();
-                    if quote_count % 2 == 0 {
-                        // Even number of quotes, so // is not inside quotes
-                        before_comment.to_string()
-                    } else {
-                        // Odd number of quotes, so // is inside quotes
-                        line.to_string()
+        enum State {
+            Code,
+            String,
+            Escape,
+            LineComment,
+            BlockComment,
+        }
+
+        let mut stripped = String::with_capacity(content.len());
+        let mut chars = content.chars().peekable();
+        let mut state = State::Code;
+        while let Some(c) = chars.next() {
+            state = match state {
+                State::Code => match (c, chars.peek()) {
+                    ('/', Some('/')) => {
+                        chars.next();
+                        State::LineComment
+                    }
+                    ('/', Some('*')) => {
+                        chars.next();
+                        stripped.push_str("  ");
+                        State::BlockComment
+                    }
+                    _ => {
+                        stripped.push(c);
+                        if c == '"' { State::String } else { State::Code }
+                    }
+                },
+                State::String => {
+                    stripped.push(c);
+                    match c {
+                        '\\' => State::Escape,
+                        '"' => State::Code,
+                        _ => State::String,
                     }
-                } else {
-                    line.to_string()
                 }
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
+                State::Escape => {
+                    stripped.push(c);
+                    State::String
+                }
+                State::LineComment if c == '\n' => {
+                    stripped.push(c);
+                    State::Code
+                }
+                State::LineComment => State::LineComment,
+                // Block comments don't nest: the first `*/` ends one
+                State::BlockComment if c == '*' && chars.peek() == Some(&'/') => {
+                    chars.next();
+                    stripped.push_str("  ");
+                    State::Code
+                }
+                State::BlockComment => {
+                    stripped.push(if c == '\n' { '\n' } else { ' ' });
+                    State::BlockComment
+                }
+            };
+        }
+        stripped
+    }
+
+    /// Replace commas directly before a closing `]` or `}` (whitespace aside) with spaces,
+    /// leaving string literals alone. Expects comments to be stripped already.
+    fn strip_trailing_commas(content: &str) -> String {
+        let mut stripped = String::with_capacity(content.len());
+        let mut in_string = false;
+        let mut escaped = false;
+        for (index, c) in content.char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+            } else if c == '"' {
+                in_string = true;
+            } else if c == ',' && content[index + 1..].trim_start().starts_with(['}', ']']) {
+                stripped.push(' ');
+                continue;
+            }
+            stripped.push(c);
+        }
+        stripped
     }
 
+    /// Resolve the directory this tool lives in under `tools_dir`
+    pub fn repo_dir(&self, tools_dir: &Path) -> Result<PathBuf> {
+        let dir_name = match &self.repo.install_dir {
+            Some(install_dir) => self.interpolate(install_dir),
+            None => return Ok(tools_dir.join(&self.repo.name)),
+        };
+
+        let mut components = Path::new(&dir_name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => Ok(tools_dir.join(dir_name)),
+            _ => Err(crate::error::Error::Config(format!(
+                "install_dir must be a single directory name: {}",
+                dir_name
+            ))),
+        }
+    }
+
+    /// Resolve `[[repo.url]]`, `[[repo.name]]`, `[[repo.ref]]` and `[[env.NAME]]` in `text`
     pub fn interpolate(&self, text: &str) -> String {
-        text.replace("[[repo.url]]", &self.repo.url)
-            .replace("[[repo.name]]", &self.repo.name)
+        interpolate_tokens(text, &self.tokens(), process_env)
+    }
+
+    /// Values of the manifest's own tokens, keyed by the name inside `[[...]]`
+    fn tokens(&self) -> HashMap<&'static str, String> {
+        HashMap::from([
+            ("repo.url", self.repo.url.clone()),
+            ("repo.name", self.repo.name.clone()),
+            ("repo.ref", self.resolved_ref().to_string()),
+        ])
+    }
+
+    /// Ref to check out: the pinned `ref` if set, otherwise the default branch
+    pub fn resolved_ref(&self) -> &str {
+        self.repo
+            .git_ref
+            .as_deref()
+            .unwrap_or(&self.repo.default_branch.name)
+    }
+
+    /// Interpolate `text`, also resolving `[[install.dir]]` to the tool's install directory
+    /// and `[[workspace.dir]]` to the invoking workspace, with `[[env.NAME]]` read from `env`
+    pub fn interpolate_install(
+        &self,
+        text: &str,
+        install_dir: &Path,
+        workspace_dir: &Path,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> String {
+        let mut tokens = self.tokens();
+        tokens.insert("install.dir", install_dir.display().to_string());
+        tokens.insert("workspace.dir", workspace_dir.display().to_string());
+        interpolate_tokens(text, &tokens, env)
+    }
+
+    /// Copy of the manifest with every action's command, script and git fields interpolated
+    pub fn resolved(&self, install_dir: &Path, workspace_dir: &Path) -> Config {
+        let mut config = self.clone();
+        let resolve = |text: &mut Option<String>| {
+            if let Some(text) = text {
+                *text = self.interpolate_install(text, install_dir, workspace_dir, process_env);
+            }
+        };
+
+        for action in config.actions.all_mut() {
+            resolve(&mut action.command);
+            resolve(&mut action.script);
+            resolve(&mut action.url);
+            resolve(&mut action.branch);
+        }
+        config
+    }
+
+    /// Apply a `<dotpath>=<value>` override (from `--set`) to the parsed manifest
+    pub fn apply_override(&mut self, assignment: &str) -> Result<()> {
+        let (path, value) = assignment.split_once('=').ok_or_else(|| {
+            crate::error::Error::Config(format!(
+                "Invalid override '{}': expected <dotpath>=<value>",
+                assignment
+            ))
+        })?;
+
+        let field = match path.trim() {
+            "repo.url" => &mut self.repo.url,
+            "repo.name" => &mut self.repo.name,
+            "repo.default_branch.name" => &mut self.repo.default_branch.name,
+            other => {
+                return Err(crate::error::Error::Config(format!(
+                    "Unsupported override path '{}': expected repo.url, repo.name or repo.default_branch.name",
+                    other
+                )));
+            }
+        };
+        *field = value.to_string();
+        Ok(())
+    }
+}
+
+/// Parse `.env`-style content: `KEY=VALUE` lines with optional `export ` prefixes and
+/// surrounding quotes; blank lines and `#` comments are skipped
+pub fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = [('"', '"'), ('\'', '\'')]
+                .iter()
+                .find_map(|(open, close)| {
+                    value
+                        .strip_prefix(*open)
+                        .and_then(|rest| rest.strip_suffix(*close))
+                })
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Workspace root for `cwd`: the nearest enclosing git, Mercurial or Jujutsu checkout
+/// when `vcs_root` is set, otherwise (or when there is none) `cwd` itself
+pub fn workspace_dir(cwd: &Path, vcs_root: bool) -> PathBuf {
+    if vcs_root
+        && let Some(root) = cwd.ancestors().find(|dir| {
+            [".git", ".hg", ".jj"]
+                .iter()
+                .any(|marker| dir.join(marker).exists())
+        })
+    {
+        return root.to_path_buf();
+    }
+    cwd.to_path_buf()
+}
+
+/// Replace each `[[name]]` in `text` in a single left-to-right pass. Names in `tokens`
+/// take their value and `env.NAME` the variable `NAME` from `env`; unknown names and
+/// unset variables are kept as written. Substituted values are never scanned again.
+///
+/// `$VAR` references are left alone for the shell, which sees the action's environment.
+pub fn interpolate_tokens(
+    text: &str,
+    tokens: &HashMap<&str, String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> String {
+    let lookup = |name: &str| {
+        tokens
+            .get(name)
+            .cloned()
+            .or_else(|| name.strip_prefix("env.").and_then(&env))
+    };
+
+    let mut interpolated = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        interpolated.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("]]") else {
+            rest = &rest[start..];
+            break;
+        };
+        match lookup(&after_open[..end]) {
+            Some(value) => {
+                interpolated.push_str(&value);
+                rest = &after_open[end + 2..];
+            }
+            None => {
+                // Keep the brackets and look for a token starting inside them
+                interpolated.push_str("[[");
+                rest = after_open;
+            }
+        }
+    }
+    interpolated.push_str(rest);
+    interpolated
+}
+
+/// Variable lookup for `[[env.NAME]]` outside an action: the process environment
+pub fn process_env(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Placeholders a starter manifest may use for the new tool's name and repository URL
+const STARTER_NAME: &str = "{{name}}";
+const STARTER_URL: &str = "{{url}}";
+
+/// Starter used by `manifests new` when no `--from` template is given
+pub const BLANK_MANIFEST: &str = r#"{
+  // Generated by `manifests new`
+  "repo": {
+    "name": "{{name}}",
+    "url": "{{url}}",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "Clone the repository", "type": "git_clone" }
+    ],
+    "update": [
+      { "seq-id": 1, "description": "Pull the latest changes", "type": "git_pull" }
+    ],
+    "build": [],
+    "run": []
+  }
+}
+"#;
+
+/// Fill a starter manifest's `{{name}}` and `{{url}}` placeholders; a starter using
+/// `{{url}}` needs a `url`
+pub fn render_starter(template: &str, name: &str, url: Option<&str>) -> Result<String> {
+    let rendered = template.replace(STARTER_NAME, name);
+    match url {
+        Some(url) => Ok(rendered.replace(STARTER_URL, url)),
+        None if rendered.contains(STARTER_URL) => Err(crate::error::Error::Config(
+            "The starter manifest uses {{url}}; pass --url".to_string(),
+        )),
+        None => Ok(rendered),
+    }
+}
+
+/// Require a tool name to be a single plain file name, so it can't reach outside a manifest
+/// directory (or url source) when joined onto it
+pub fn validate_tool_name(tool_name: &str) -> Result<()> {
+    let mut components = Path::new(tool_name).components();
+    let single_component = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    if !single_component || tool_name.contains(['/', '\\']) {
+        return Err(crate::error::Error::Config(format!(
+            "Invalid tool name '{}': must be a plain name without path separators or '..'",
+            tool_name
+        )));
+    }
+    Ok(())
+}
+
+/// Warn when a manifest (or its directory) can be modified by any user; refuse when `strict`
+pub fn check_manifest_permissions(manifest_path: &Path, strict: bool) -> Result<()> {
+    let Some(insecure_path) = world_writable_location(manifest_path) else {
+        return Ok(());
+    };
+
+    if strict {
+        return Err(crate::error::Error::Config(format!(
+            "Refusing to load manifest {}: {} is world-writable",
+            manifest_path.display(),
+            insecure_path.display()
+        )));
+    }
+
+    eprintln!(
+        "{} {} is world-writable; other users could change the commands in {}",
+        marker(Marker::Warn),
+        insecure_path.display(),
+        manifest_path.display()
+    );
+    Ok(())
+}
+
+/// The manifest file or its directory, whichever is world-writable first
+#[cfg(unix)]
+fn world_writable_location(manifest_path: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = match manifest_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    [manifest_path, dir]
+        .into_iter()
+        .find(|path| {
+            std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o002 != 0)
+        })
+        .map(Path::to_path_buf)
+}
+
+#[cfg(not(unix))]
+fn world_writable_location(_manifest_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const MANIFEST_BODY: &str = r#"
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+
+    #[test]
+    fn test_load_accepts_schema_and_comment() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tool.jsonc");
+        let manifest = format!(
+            r#"{{
+  "$schema": "https://example.com/manifest.schema.json",
+  "_comment": "annotations are allowed",
+  "repo": {{ "name": "tool", "url": "https://example.com/tool.git", "default_branch": {{ "name": "main" }} }},{}"#,
+            MANIFEST_BODY
+        );
+        fs::write(&path, manifest).unwrap();
+
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(
+            config.schema.as_deref(),
+            Some("https://example.com/manifest.schema.json")
+        );
+        assert_eq!(config.repo.name, "tool");
+    }
+
+    fn strip_and_parse(content: &str) -> serde_json::Value {
+        serde_json::from_str(&Config::strip_comments(content)).unwrap()
+    }
+
+    #[test]
+    fn test_strip_comments_removes_block_comments() {
+        let value = strip_and_parse(
+            r#"{
+  /* Install steps:
+     clone, then build */
+  "a": /* inline */ 1,
+  "b": 2 /* trailing */
+}"#,
+        );
+        assert_eq!(value, serde_json::json!({ "a": 1, "b": 2 }));
+    }
+
+    #[test]
+    fn test_strip_comments_ignores_block_start_in_line_comment() {
+        let value = strip_and_parse(
+            r#"{
+  "a": 1, // see /* not a block comment
+  "b": 2
+}"#,
+        );
+        assert_eq!(value, serde_json::json!({ "a": 1, "b": 2 }));
+    }
+
+    #[test]
+    fn test_strip_comments_preserves_markers_in_strings() {
+        let value = strip_and_parse(
+            r#"{
+  /* comment */ "a": "ends with */ here",
+  "b": "/* not a comment */ // nor this",
+  "c": "escaped \" /* quote */"
+}"#,
+        );
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "a": "ends with */ here",
+                "b": "/* not a comment */ // nor this",
+                "c": "escaped \" /* quote */"
+            })
+        );
+    }
+
+    #[test]
+    fn test_strip_comments_block_comments_do_not_nest() {
+        let value = strip_and_parse(r#"{ /* /* */ "a": 1 }"#);
+        assert_eq!(value, serde_json::json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn test_strip_comments_keeps_error_positions() {
+        let content = "{\n  /* one\n     two */ \"a\": 1,\n  \"b\": oops\n}";
+        let error = serde_json::from_str::<serde_json::Value>(&Config::strip_comments(content))
+            .unwrap_err();
+        assert_eq!((error.line(), error.column()), (4, 8));
+    }
+
+    #[test]
+    fn test_strip_trailing_commas_leaves_strings_alone() {
+        let content = r#"{ "a": [1, 2,  ], "b": { "c": ",}" , }, "d": "x\",]", }"#;
+        let value: serde_json::Value =
+            serde_json::from_str(&Config::strip_trailing_commas(content)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "a": [1, 2], "b": { "c": ",}" }, "d": "x\",]" })
+        );
+    }
+
+    #[test]
+    fn test_load_accepts_trailing_commas() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tool.jsonc");
+        let manifest = r#"{
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" }, },
+  "dependencies": [
+    { "name": "make", "version": "4", "url": "https://www.gnu.org/software/make/" },
+  ],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [
+      { "seq-id": 1, "description": "launch", "command": "tool --sep ',}'" },
+      { "seq-id": 2, "description": "again", "command": "tool" }, // last one
+    ],
+  },
+}"#;
+        fs::write(&path, manifest).unwrap();
+
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(config.dependencies.len(), 1);
+        assert_eq!(config.actions.run.len(), 2);
+        assert_eq!(
+            config.actions.run[0].command.as_deref(),
+            Some("tool --sep ',}'")
+        );
+    }
+
+    fn manifest_with_run_action(fields: &str) -> String {
+        format!(
+            r#"{{
+  "repo": {{ "name": "tool", "url": "https://example.com/tool.git", "default_branch": {{ "name": "main" }} }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [], "update": [], "build": [],
+    "run": [{{ "seq-id": 3, "description": "launch", "command": "tool", {} }}]
+  }}
+}}"#,
+            fields
+        )
+    }
+
+    #[test]
+    fn test_load_rejects_spawn_with_interactive_or_timeout() {
+        let temp_dir = tempdir().unwrap();
+
+        for (index, (fields, conflict)) in [
+            (
+                r#""spawn": true, "interactive": true"#,
+                "spawn and interactive",
+            ),
+            (
+                r#""spawn": true, "timeout_secs": 5"#,
+                "spawn and timeout_secs",
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let path = temp_dir.path().join(format!("tool{}.jsonc", index));
+            fs::write(&path, manifest_with_run_action(fields)).unwrap();
+            let message = Config::load_from_path(&path).unwrap_err().to_string();
+            assert!(
+                message.contains(&format!("Step 3 (launch) sets both {}", conflict)),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_rejects_manifest_requiring_newer_version() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tool.jsonc");
+        let manifest = manifest_with_run_action(r#""spawn": false"#).replacen(
+            '{',
+            r#"{ "min_version": "999.0.0","#,
+            1,
+        );
+        fs::write(&path, manifest).unwrap();
+
+        let message = Config::load_from_path(&path).unwrap_err().to_string();
+        assert!(
+            message.contains("requires tool-meister 999.0.0 or newer"),
+            "{}",
+            message
+        );
+        assert!(message.contains("please upgrade"), "{}", message);
+    }
+
+    #[test]
+    fn test_min_version_accepts_equal_or_older() {
+        let mut config: Config =
+            serde_json::from_str(&manifest_with_run_action(r#""spawn": false"#)).unwrap();
+
+        for required in ["0.2.0", "0.1.9", "0.2.0-beta.1"] {
+            config.min_version = Some(required.to_string());
+            assert!(config.check_min_version("0.2.0").is_ok(), "{}", required);
+        }
+
+        config.min_version = Some("0.2.1".to_string());
+        assert!(config.check_min_version("0.2.0").is_err());
+        config.min_version = Some("latest".to_string());
+        assert!(config.check_min_version("0.2.0").is_err());
+    }
+
+    #[test]
+    fn test_load_reports_invalid_seq_id_clearly() {
+        let temp_dir = tempdir().unwrap();
+
+        for (index, (seq_id, expected)) in [
+            (
+                r#""first""#,
+                "expected seq-id to be a whole number from 0 to 4294967295",
+            ),
+            ("-1", "seq-id -1 is negative"),
+            ("4294967296", "seq-id 4294967296 is too large"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let path = temp_dir.path().join(format!("tool{}.jsonc", index));
+            let manifest = manifest_with_run_action(r#""spawn": false"#)
+                .replace(r#""seq-id": 3"#, &format!(r#""seq-id": {}"#, seq_id));
+            fs::write(&path, manifest).unwrap();
+
+            let message = Config::load_from_path(&path).unwrap_err().to_string();
+            assert!(message.contains(expected), "{}", message);
+            assert!(
+                message.contains(&format!("tool{}.jsonc:6:", index)),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_spawn_falls_back_to_phase_default() {
+        let mut action = action(Some("serve"), None);
+        assert!(!action.spawns(false));
+        assert!(action.spawns(true));
+
+        action.spawn = Some(false);
+        assert!(!action.spawns(true));
+
+        action.spawn = None;
+        action.interactive = true;
+        assert!(!action.spawns(true));
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_spawn_default_phase() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tool.jsonc");
+        let manifest = manifest_with_run_action(r#""spawn": false"#).replace(
+            r#""installation": [],"#,
+            r#""spawn_default": ["serve"], "installation": [],"#,
+        );
+        fs::write(&path, manifest).unwrap();
+
+        let message = Config::load_from_path(&path).unwrap_err().to_string();
+        assert!(
+            message.contains("Unknown phase 'serve' in spawn_default"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_seq_id_warnings_flag_zero_and_huge_ids() {
+        let mut config: Config =
+            serde_json::from_str(&manifest_with_run_action(r#""spawn": false"#)).unwrap();
+        assert!(config.seq_id_warnings().is_empty());
+
+        config.actions.run[0].seq_id = 0;
+        let warnings = config.seq_id_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].contains("seq-id 0, which is reserved"),
+            "{:?}",
+            warnings
+        );
+
+        config.actions.run[0].seq_id = MAX_SEQ_ID + 1;
+        assert!(config.seq_id_warnings()[0].contains("unusually large"));
+    }
+
+    #[test]
+    fn test_load_accepts_compatible_action_settings() {
+        let temp_dir = tempdir().unwrap();
+
+        for (index, fields) in [
+            r#""spawn": true"#,
+            r#""interactive": true, "timeout_secs": 60"#,
+            r#""spawn": false, "interactive": true"#,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let path = temp_dir.path().join(format!("tool{}.jsonc", index));
+            fs::write(&path, manifest_with_run_action(fields)).unwrap();
+            assert!(Config::load_from_path(&path).is_ok(), "{}", fields);
+        }
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_top_level_key() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tool.jsonc");
+        let manifest = format!(
+            r#"{{
+  "reppo": {{ "name": "tool", "url": "https://example.com/tool.git", "default_branch": {{ "name": "main" }} }},{}"#,
+            MANIFEST_BODY
+        );
+        fs::write(&path, manifest).unwrap();
+
+        let err = Config::load_from_path(&path).unwrap_err();
+        let message = match err {
+            crate::error::Error::Anyhow(e) => format!("{:#}", e),
+            other => other.to_string(),
+        };
+        assert!(message.contains("unknown field `reppo`"));
+    }
+
+    #[test]
+    fn test_load_from_path_caches_until_modified() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tool.jsonc");
+        let manifest = |name: &str| {
+            format!(
+                r#"{{
+  "repo": {{ "name": "{}", "url": "https://example.com/tool.git", "default_branch": {{ "name": "main" }} }},{}"#,
+                name, MANIFEST_BODY
+            )
+        };
+        let set_mtime = |secs: u64| {
+            let mtime = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(mtime)
+                .unwrap();
+        };
+
+        fs::write(&path, manifest("first")).unwrap();
+        set_mtime(1_000);
+        assert_eq!(Config::load_from_path(&path).unwrap().repo.name, "first");
+
+        // Same mtime: the cached copy is served even though the content changed
+        fs::write(&path, manifest("second")).unwrap();
+        set_mtime(1_000);
+        assert_eq!(Config::load_from_path(&path).unwrap().repo.name, "first");
+
+        // New mtime: the file is parsed again
+        set_mtime(2_000);
+        assert_eq!(Config::load_from_path(&path).unwrap().repo.name, "second");
+    }
+
+    fn action(command: Option<&str>, script: Option<&str>) -> Action {
+        Action {
+            seq_id: 1,
+            name: None,
+            command: command.map(String::from),
+            script: script.map(String::from),
+            description: "step".to_string(),
+            spawn: None,
+            shell: None,
+            interactive: false,
+            timeout_secs: None,
+            os: Vec::new(),
+            kind: None,
+            url: None,
+            branch: None,
+        }
+    }
+
+    #[test]
+    fn test_action_body_requires_exactly_one_of_command_and_script() {
+        assert_eq!(
+            action(Some("make"), None).body().unwrap(),
+            ActionBody::Command("make")
+        );
+        assert_eq!(
+            action(None, Some("build.sh")).body().unwrap(),
+            ActionBody::Script("build.sh")
+        );
+
+        let both = action(Some("make"), Some("build.sh")).body().unwrap_err();
+        assert!(both.to_string().contains("sets both command and script"));
+
+        let neither = action(None, None).body().unwrap_err();
+        assert!(neither.to_string().contains("needs a command or a script"));
+    }
+
+    #[test]
+    fn test_action_type_parses_typed_and_bare_actions() {
+        let actions: Vec<Action> = serde_json::from_str(
+            r#"[
+  { "seq-id": 1, "description": "bare", "command": "make" },
+  { "seq-id": 2, "description": "shell", "type": "shell", "command": "make" },
+  { "seq-id": 3, "description": "clone", "type": "git_clone", "branch": "dev" },
+  { "seq-id": 4, "description": "pull", "type": "git_pull" }
+]"#,
+        )
+        .unwrap();
+
+        let bodies: Vec<_> = actions.iter().map(|a| a.body().unwrap()).collect();
+        assert_eq!(
+            bodies,
+            vec![
+                ActionBody::Command("make"),
+                ActionBody::Command("make"),
+                ActionBody::GitClone {
+                    url: None,
+                    branch: Some("dev")
+                },
+                ActionBody::GitPull,
+            ]
+        );
+
+        let mut clone = actions[2].clone();
+        clone.command = Some("git clone x".to_string());
+        assert!(
+            clone
+                .body()
+                .unwrap_err()
+                .to_string()
+                .contains("built-in git action")
+        );
+
+        let mut shell = actions[1].clone();
+        shell.branch = Some("dev".to_string());
+        assert!(
+            shell
+                .body()
+                .unwrap_err()
+                .to_string()
+                .contains("only git_clone actions take")
+        );
+    }
+
+    #[test]
+    fn test_action_type_rejects_unknown_types_and_bad_fields() {
+        let parse = |action: &str| {
+            serde_json::from_str::<Action>(action)
+                .unwrap_err()
+                .to_string()
+        };
+
+        let typo = parse(
+            r#"{ "seq-id": 1, "description": "clone", "type": "git_clon", "command": "rm -rf x" }"#,
+        );
+        assert!(typo.contains("unknown variant `git_clon`"), "{}", typo);
+
+        let bad_branch =
+            parse(r#"{ "seq-id": 1, "description": "clone", "type": "git_clone", "branch": 5 }"#);
+        assert!(
+            bad_branch.contains("invalid type: integer `5`"),
+            "{}",
+            bad_branch
+        );
+    }
+
+    #[test]
+    fn test_load_crlf_manifest_matches_lf() {
+        let temp_dir = tempdir().unwrap();
+        let manifest = r#"{
+  // the tool's repository
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [], // none yet
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        let lf_path = temp_dir.path().join("lf.jsonc");
+        let crlf_path = temp_dir.path().join("crlf.jsonc");
+        fs::write(&lf_path, manifest).unwrap();
+        fs::write(&crlf_path, manifest.replace('\n', "\r\n")).unwrap();
+
+        let lf = Config::load_from_path(&lf_path).unwrap();
+        let crlf = Config::load_from_path(&crlf_path).unwrap();
+        assert_eq!(
+            serde_json::to_value(&crlf).unwrap(),
+            serde_json::to_value(&lf).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_yaml_and_toml_manifests() {
+        let temp_dir = tempdir().unwrap();
+        let yaml_path = temp_dir.path().join("tool.yaml");
+        fs::write(
+            &yaml_path,
+            r#"repo:
+  name: tool
+  url: https://example.com/tool.git
+  default_branch: { name: main }
+dependencies: []
+actions:
+  installation: []
+  update: []
+  build: []
+  run:
+    - { seq-id: 1, description: launch, command: tool }
+"#,
+        )
+        .unwrap();
+        let toml_path = temp_dir.path().join("tool.toml");
+        fs::write(
+            &toml_path,
+            r#"dependencies = []
+
+[repo]
+name = "tool"
+url = "https://example.com/tool.git"
+default_branch = { name = "main" }
+
+[actions]
+installation = []
+update = []
+build = []
+run = [{ seq-id = 1, description = "launch", command = "tool" }]
+"#,
+        )
+        .unwrap();
+
+        for path in [yaml_path, toml_path] {
+            let config = Config::load_from_path(&path).unwrap();
+            assert_eq!(config.repo.name, "tool");
+            assert_eq!(
+                config.actions.run[0].body().unwrap(),
+                ActionBody::Command("tool")
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let content = "# settings\nPLAIN=value\nexport EXPORTED=1\n\nQUOTED=\"a b\"\nSINGLE='c=d'\nno separator\n";
+        assert_eq!(
+            parse_env_file(content),
+            vec![
+                ("PLAIN".to_string(), "value".to_string()),
+                ("EXPORTED".to_string(), "1".to_string()),
+                ("QUOTED".to_string(), "a b".to_string()),
+                ("SINGLE".to_string(), "c=d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_tool_name_rejects_traversal() {
+        for name in [
+            "../../etc/passwd",
+            "..",
+            ".",
+            "",
+            "nested/tool",
+            "..\\tool",
+            "/abs",
+        ] {
+            let err = validate_tool_name(name).unwrap_err();
+            assert!(err.to_string().contains("Invalid tool name"), "{:?}", name);
+        }
+        for name in ["fd", "ripgrep", "tool.v2", "my-tool_1"] {
+            assert!(validate_tool_name(name).is_ok(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_error_reports_original_line_and_column() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("tool.jsonc");
+        // Comment lines are stripped before parsing; the missing comma is on line 5
+        let manifest = r#"{
+  // the tool's repository
+  "repo": { "name": "tool", "url": "https://example.com/tool.git", "default_branch": { "name": "main" } },
+  // no dependencies yet
+  "dependencies": [] "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        fs::write(&path, manifest).unwrap();
+
+        let message = Config::load_from_path(&path).unwrap_err().to_string();
+        assert!(
+            message.contains(&format!("{}:5:22: expected `,` or `}}`", path.display())),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_apply_override_patches_repo_fields() {
+        let mut config: Config =
+            serde_json::from_str(&manifest_with_run_action(r#""spawn": false"#)).unwrap();
+
+        config
+            .apply_override("repo.url=https://example.com/fork.git")
+            .unwrap();
+        config
+            .apply_override("repo.default_branch.name=dev")
+            .unwrap();
+        assert_eq!(
+            config.interpolate("git clone [[repo.url]]"),
+            "git clone https://example.com/fork.git"
+        );
+        assert_eq!(config.repo.default_branch.name, "dev");
+
+        assert!(config.apply_override("repo.branch=dev").is_err());
+        assert!(config.apply_override("repo.url").is_err());
+    }
+
+    #[test]
+    fn test_interpolate_workspace_dir() {
+        let config: Config =
+            serde_json::from_str(&manifest_with_run_action(r#""spawn": false"#)).unwrap();
+        assert_eq!(
+            config.interpolate_install(
+                "make -C [[workspace.dir]] TOOL=[[install.dir]]",
+                Path::new("/tools/tool"),
+                Path::new("/work/monorepo"),
+                process_env,
+            ),
+            "make -C /work/monorepo TOOL=/tools/tool"
+        );
+    }
+
+    #[test]
+    fn test_workspace_dir_detects_vcs_root_when_enabled() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().join("monorepo");
+        let cwd = root.join("services/api");
+        fs::create_dir_all(&cwd).unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+
+        assert_eq!(workspace_dir(&cwd, false), cwd);
+        assert_eq!(workspace_dir(&cwd, true), root);
+
+        let outside = temp_dir.path().join("scratch");
+        fs::create_dir(&outside).unwrap();
+        assert_eq!(workspace_dir(&outside, true), outside);
+    }
+
+    #[test]
+    fn test_interpolate_repo_ref_falls_back_to_default_branch() {
+        let mut config: Config =
+            serde_json::from_str(&manifest_with_run_action(r#""spawn": false"#)).unwrap();
+        assert_eq!(
+            config.interpolate("git checkout [[repo.ref]]"),
+            "git checkout main"
+        );
+
+        config.repo.git_ref = Some("v1.2.0".to_string());
+        assert_eq!(
+            config.interpolate("git checkout [[repo.ref]]"),
+            "git checkout v1.2.0"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_tokens_in_one_pass() {
+        let mut config: Config =
+            serde_json::from_str(&manifest_with_run_action(r#""spawn": false"#)).unwrap();
+        // A value that looks like a token must come through verbatim
+        config.repo.name = "[[repo.url]]".to_string();
+
+        assert_eq!(
+            config.interpolate_install(
+                "cd [[install.dir]] && ./[[repo.name]] --ref=[[repo.ref]] [[repo.url]]",
+                Path::new("/tools/tool"),
+                Path::new("/work"),
+                process_env,
+            ),
+            "cd /tools/tool && ./[[repo.url]] --ref=main https://example.com/tool.git"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_expands_env_tokens_and_leaves_shell_variables() {
+        let tokens = HashMap::from([("repo.name", "tool".to_string())]);
+        let env = |name: &str| (name == "PREFIX").then(|| "/home/me/.local".to_string());
+
+        assert_eq!(
+            interpolate_tokens(
+                "make PREFIX=[[env.PREFIX]] NAME=[[repo.name]]",
+                &tokens,
+                env
+            ),
+            "make PREFIX=/home/me/.local NAME=tool"
+        );
+        // `$VAR` is the shell's to expand, after any `cd` and inside its own quoting rules
+        assert_eq!(
+            interpolate_tokens("echo $PREFIX '${PREFIX}' $PWD", &tokens, env),
+            "echo $PREFIX '${PREFIX}' $PWD"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_keeps_unknown_tokens() {
+        let tokens = HashMap::from([("repo.name", "tool".to_string())]);
+        let env = |name: &str| (name == "PATH").then(|| "/usr/bin".to_string());
+        assert_eq!(
+            interpolate_tokens("[[nope]] [[[[repo.name]]]] [[repo.name", &tokens, env),
+            "[[nope]] [[tool]] [[repo.name"
+        );
+        assert_eq!(
+            interpolate_tokens("[[env.UNSET]]", &tokens, env),
+            "[[env.UNSET]]"
+        );
+        assert_eq!(
+            interpolate_tokens("PATH=[[env.PATH]]", &tokens, env),
+            "PATH=/usr/bin"
+        );
+    }
+
+    #[test]
+    fn test_load_rejects_duplicate_action_names_in_a_phase() {
+        let temp_dir = tempdir().unwrap();
+        let manifest = |second_name: &str| {
+            format!(
+                r#"{{
+  "repo": {{ "name": "tool", "url": "https://example.com/tool.git", "default_branch": {{ "name": "main" }} }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [{{ "seq-id": 1, "name": "serve", "description": "setup", "command": "true" }}],
+    "update": [], "build": [],
+    "run": [
+      {{ "seq-id": 1, "name": "serve", "description": "serve", "command": "true" }},
+      {{ "seq-id": 2, "name": "{}", "description": "second", "command": "true" }}
+    ]
+  }}
+}}"#,
+                second_name
+            )
+        };
+
+        let duplicate = temp_dir.path().join("duplicate.jsonc");
+        fs::write(&duplicate, manifest("serve")).unwrap();
+        let message = Config::load_from_path(&duplicate).unwrap_err().to_string();
+        assert!(
+            message.contains("Duplicate action name 'serve' in the run phase"),
+            "{}",
+            message
+        );
+
+        let distinct = temp_dir.path().join("distinct.jsonc");
+        fs::write(&distinct, manifest("watch")).unwrap();
+        assert!(Config::load_from_path(&distinct).is_ok());
+    }
+
+    #[test]
+    fn test_blank_starter_renders_to_valid_manifest() {
+        let manifest =
+            render_starter(BLANK_MANIFEST, "fd", Some("https://github.com/sharkdp/fd")).unwrap();
+        let config = Config::parse(&manifest, Path::new("fd.jsonc")).unwrap();
+        assert_eq!(config.repo.name, "fd");
+        assert_eq!(config.repo.url, "https://github.com/sharkdp/fd");
+
+        assert!(render_starter(BLANK_MANIFEST, "fd", None).is_err());
     }
 }
@@ -0,0 +1,120 @@
+use crate::global_config::{AuthMethod, ManifestSource};
+use crate::output::{Marker, marker};
+
+/// Keyring service name credentials are stored under
+const KEYRING_SERVICE: &str = env!("CARGO_PKG_NAME");
+
+/// Environment variable read when a source doesn't name its own
+const DEFAULT_TOKEN_ENV: &str = "TOOL_MEISTER_TOKEN";
+
+/// Backend holding secrets, keyed by source path
+pub trait SecretStore {
+    /// The secret for `key`, `Ok(None)` if there is none, or an error if the store is unavailable
+    fn lookup(&self, key: &str) -> Result<Option<String>, String>;
+}
+
+/// The operating system's secret store
+pub struct OsKeyring;
+
+impl SecretStore for OsKeyring {
+    fn lookup(&self, key: &str) -> Result<Option<String>, String> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, key).map_err(|e| e.to_string())?;
+        match entry.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Token to send when fetching `source`, resolved from the OS keyring or the environment
+pub fn source_token(source: &ManifestSource) -> Option<String> {
+    resolve_token(source, &OsKeyring, |name| std::env::var(name).ok())
+}
+
+/// Resolve a source's token; keyring sources fall back to the environment variable
+/// when the keyring has no entry or can't be reached
+pub fn resolve_token(
+    source: &ManifestSource,
+    store: &dyn SecretStore,
+    env: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    let auth = source.auth.as_ref()?;
+    let token_env = auth.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV);
+
+    if auth.method == AuthMethod::Keyring {
+        match store.lookup(&source.path) {
+            Ok(Some(token)) => return Some(token),
+            Ok(None) => {}
+            Err(e) => eprintln!(
+                "{} Keyring unavailable ({}); falling back to ${}",
+                marker(Marker::Warn),
+                e,
+                token_env
+            ),
+        }
+    }
+
+    env(token_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global_config::SourceAuth;
+    use std::collections::HashMap;
+
+    struct MockStore(Result<HashMap<String, String>, String>);
+
+    impl SecretStore for MockStore {
+        fn lookup(&self, key: &str) -> Result<Option<String>, String> {
+            match &self.0 {
+                Ok(secrets) => Ok(secrets.get(key).cloned()),
+                Err(e) => Err(e.clone()),
+            }
+        }
+    }
+
+    fn source(method: AuthMethod, token_env: Option<&str>) -> ManifestSource {
+        ManifestSource {
+            source_type: "url".to_string(),
+            path: "https://example.com/manifests".to_string(),
+            branch: None,
+            subdir: None,
+            auto_update: true,
+            auth: Some(SourceAuth {
+                method,
+                token_env: token_env.map(String::from),
+            }),
+        }
+    }
+
+    fn env(name: &str) -> Option<String> {
+        (name == "TOOL_MEISTER_TOKEN").then(|| "from-env".to_string())
+    }
+
+    #[test]
+    fn test_resolve_token_reads_keyring_entry_for_source() {
+        let store = MockStore(Ok(HashMap::from([(
+            "https://example.com/manifests".to_string(),
+            "from-keyring".to_string(),
+        )])));
+
+        let token = resolve_token(&source(AuthMethod::Keyring, None), &store, env);
+        assert_eq!(token.as_deref(), Some("from-keyring"));
+    }
+
+    #[test]
+    fn test_resolve_token_falls_back_to_env() {
+        let missing = MockStore(Ok(HashMap::new()));
+        let token = resolve_token(&source(AuthMethod::Keyring, None), &missing, env);
+        assert_eq!(token.as_deref(), Some("from-env"));
+
+        let unavailable = MockStore(Err("no secret service".to_string()));
+        let token = resolve_token(&source(AuthMethod::Keyring, None), &unavailable, env);
+        assert_eq!(token.as_deref(), Some("from-env"));
+
+        let custom_env = resolve_token(&source(AuthMethod::Env, Some("OTHER")), &missing, env);
+        assert_eq!(custom_env, None);
+    }
+}
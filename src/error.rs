@@ -20,8 +20,31 @@ pub enum Error {
     #[error("Command execution failed: {0}")]
     Command(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    #[error("Cancelled")]
+    Cancelled,
+
     #[error("Anyhow error: {0}")]
     Anyhow(#[from] anyhow::Error),
 }
 
+impl Error {
+    /// Process exit code for this class of failure, listed in `--help`
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Config(_) | Error::TomlDe(_) | Error::TomlSer(_) | Error::JsonDe(_) => 2,
+            Error::NotFound(_) => 3,
+            Error::Command(_) => 4,
+            Error::Network(_) => 5,
+            Error::Cancelled => 130,
+            Error::Io(_) | Error::Anyhow(_) => 1,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,16 +1,37 @@
 use crate::error::{Error, Result};
+use crate::output::{Marker, marker};
+use crate::remote::NetworkPolicy;
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
     env,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+/// Forbid creating or changing the app configuration file for the rest of the process
+pub fn set_locked(locked: bool) {
+    LOCKED.store(locked, Ordering::Relaxed);
+}
+
+/// Whether a `TOOL_MEISTER_LOCKED` value turns locked mode on: anything but empty, `0` or `false`
+pub fn locked_env_value(value: Option<&str>) -> bool {
+    value.is_some_and(|value| !matches!(value.trim(), "" | "0" | "false"))
+}
+
+/// Extensions of the global config formats, probed in order when the configured file is missing
+const CONFIG_FORMATS: [&str; 4] = ["toml", "yaml", "yml", "json"];
+
+#[allow(dead_code)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CargoMetadata {
     pub settings: MetadataSettings,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MetadataSettings {
     pub defaults: DefaultSettings,
@@ -25,17 +46,89 @@ pub struct DefaultSettings {
     pub tools_sources_path: String,
 }
 
+/// Manifest sources paired with their index in `manifest_sources`
+type IndexedSources<'a> = Vec<(usize, &'a ManifestSource)>;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GlobalConfig {
     /// Paths to search for tool manifests (local directories and URLs)
     pub manifest_sources: Vec<ManifestSource>,
-    /// Base directory where tools should be installed/downloaded
+    /// Base directory where tools should be installed/downloaded; a relative path
+    /// is resolved against the directory holding the config file
     pub tools_dir: PathBuf,
     /// Default manifest directory
     pub default_manifest_dir: PathBuf,
+    /// Upper bound on tasks run at once by parallel operations
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Whether `spawn` actions detach: "auto" (decide from arguments), "always" or "never"
+    #[serde(default)]
+    pub spawn_mode: SpawnMode,
+    /// Manifest file extensions `find_tool_manifest` probes, in order of precedence
+    #[serde(default = "default_manifest_formats")]
+    pub manifest_formats: Vec<String>,
+    /// Directory receiving a log file per executed step, as `<tool>/<phase>/<seq_id>.log`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<PathBuf>,
+    /// Seconds a git or url fetch may spend connecting or stalled before it fails
+    #[serde(default = "default_network_timeout_secs")]
+    pub network_timeout_secs: u64,
+    /// Further attempts after a transient network failure, with exponential backoff
+    #[serde(default = "default_network_retries")]
+    pub network_retries: u32,
+    /// `GIT_SSH_COMMAND` used for git sources (e.g. "ssh -i ~/.ssh/corp_key"); by default
+    /// plain `ssh` with the network timeout as its ConnectTimeout, so the user's agent and
+    /// known_hosts still apply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_ssh_command: Option<String>,
+    /// Resolve `[[workspace.dir]]` to the enclosing VCS root instead of the current directory
+    #[serde(default)]
+    pub workspace_vcs_root: bool,
+    /// Per-invocation override of every source's `auto_update` (never saved)
+    #[serde(skip)]
+    pub update_sources: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Global override for the spawn-vs-wait heuristic of `spawn` actions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpawnMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// A tool's manifest as found by `find_tool_manifest`
+#[derive(Debug, Clone)]
+pub struct ResolvedManifest<'a> {
+    pub path: PathBuf,
+    /// The source providing it
+    pub source: &'a ManifestSource,
+    /// Position of the source in `manifest_sources`
+    pub index: usize,
+}
+
+impl ResolvedManifest<'_> {
+    /// Where the manifest came from, e.g. `git:https://host/repo.git (source 2, cached)`;
+    /// manifests of git and url sources are read from their local cache
+    pub fn provenance(&self) -> String {
+        let cached = if self.source.source_type == "local" {
+            ""
+        } else {
+            ", cached"
+        };
+        format!(
+            "{}:{} (source {}{})",
+            self.source.source_type,
+            self.source.path,
+            self.index + 1,
+            cached
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ManifestSource {
     /// Type of source: "local", "git", "url"
     #[serde(rename = "type")]
@@ -44,15 +137,71 @@ pub struct ManifestSource {
     pub path: String,
     /// Optional branch for git sources
     pub branch: Option<String>,
+    /// Optional subdirectory holding the manifests (local and git sources)
+    pub subdir: Option<String>,
     /// Whether this source should be updated automatically
     #[serde(default = "default_auto_update")]
     pub auto_update: bool,
+    /// How to find the credential sent when fetching this source (url sources)
+    #[serde(default)]
+    pub auth: Option<SourceAuth>,
+}
+
+/// Credential lookup for a private source
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SourceAuth {
+    pub method: AuthMethod,
+    /// Environment variable holding the token (defaults to `TOOL_MEISTER_TOKEN`)
+    pub token_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMethod {
+    /// Read the token from an environment variable
+    Env,
+    /// Read the token from the OS keyring (keyed by the source path), falling back to the environment
+    Keyring,
 }
 
 fn default_auto_update() -> bool {
     true
 }
 
+fn default_max_concurrency() -> usize {
+    4
+}
+
+fn default_network_timeout_secs() -> u64 {
+    30
+}
+
+fn default_network_retries() -> u32 {
+    2
+}
+
+fn default_manifest_formats() -> Vec<String> {
+    ["jsonc", "yaml", "yml", "toml"].map(String::from).to_vec()
+}
+
+/// Source types `find_tool_manifest` knows how to search
+const SOURCE_TYPES: [&str; 3] = ["local", "git", "url"];
+
+impl ManifestSource {
+    /// Whether the source's type is one that can be searched
+    pub fn has_known_type(&self) -> bool {
+        SOURCE_TYPES.contains(&self.source_type.as_str())
+    }
+
+    /// Join the optional `subdir` onto the directory a source resolves to
+    pub fn join_subdir(&self, base: PathBuf) -> PathBuf {
+        match &self.subdir {
+            Some(subdir) => base.join(subdir),
+            None => base,
+        }
+    }
+}
+
 impl Default for GlobalConfig {
     fn default() -> Self {
         // Try to load defaults from Cargo.toml metadata, fallback to hardcoded defaults
@@ -65,11 +214,22 @@ impl Default for GlobalConfig {
                         source_type: "local".to_string(),
                         path: "manifests".to_string(),
                         branch: None,
+                        subdir: None,
+                        auth: None,
                         auto_update: false,
                     }],
                     tools_dir: PathBuf::from("tools"),
 
                     default_manifest_dir: PathBuf::from("manifests"),
+                    max_concurrency: default_max_concurrency(),
+                    spawn_mode: SpawnMode::default(),
+                    manifest_formats: default_manifest_formats(),
+                    output_dir: None,
+                    network_timeout_secs: default_network_timeout_secs(),
+                    network_retries: default_network_retries(),
+                    git_ssh_command: None,
+                    workspace_vcs_root: false,
+                    update_sources: None,
                 }
             }
         }
@@ -89,14 +249,47 @@ impl GlobalConfig {
                 )
             })?;
 
-            let config: GlobalConfig = toml::from_str(&content).with_context(|| {
-                format!(
-                    "Failed to parse global config file: {}",
-                    config_path.display()
-                )
-            })?;
+            match Self::parse_config(&content, &config_path) {
+                Ok(config) => {
+                    config.warn_duplicate_sources();
+                    Ok(config)
+                }
+                Err(e) if LOCKED.load(Ordering::Relaxed) => Err(Error::Config(format!(
+                    "Failed to parse global config file {}: {}",
+                    config_path.display(),
+                    e
+                ))),
+                Err(e) => {
+                    // Keep the broken file around and fall back to defaults
+                    let backup_path = Self::backup_path(&config_path);
+                    std::fs::rename(&config_path, &backup_path).with_context(|| {
+                        format!(
+                            "Failed to back up corrupt global config file: {}",
+                            config_path.display()
+                        )
+                    })?;
+                    eprintln!(
+                        "{} Failed to parse global config file {}: {}",
+                        marker(Marker::Warn),
+                        config_path.display(),
+                        e
+                    );
+                    eprintln!(
+                        "{} Moved it to {} and continuing with defaults",
+                        marker(Marker::Warn),
+                        backup_path.display()
+                    );
 
-            Ok(config)
+                    let default_config = GlobalConfig::default();
+                    default_config.save()?;
+                    Ok(default_config)
+                }
+            }
+        } else if LOCKED.load(Ordering::Relaxed) {
+            Err(Error::Config(format!(
+                "No app configuration at {}, and locked mode forbids creating one",
+                config_path.display()
+            )))
         } else {
             // Create default config file
             let default_config = GlobalConfig::default();
@@ -105,21 +298,66 @@ impl GlobalConfig {
         }
     }
 
+    fn backup_path(config_path: &Path) -> PathBuf {
+        let mut file_name = config_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".bak");
+        config_path.with_file_name(file_name)
+    }
+
     pub fn save(&self) -> Result<()> {
         let config_path = Self::get_config_path();
+        if LOCKED.load(Ordering::Relaxed) {
+            return Err(Error::Config(format!(
+                "The app configuration is locked (--locked or TOOL_MEISTER_LOCKED); refusing to change {}",
+                config_path.display()
+            )));
+        }
 
         // Create parent directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| Error::Io(e))?;
+            std::fs::create_dir_all(parent).map_err(Error::Io)?;
         }
 
-        let toml_content = toml::to_string_pretty(self).map_err(|e| Error::TomlSer(e))?;
+        let content = self.serialize_config(&config_path)?;
 
-        std::fs::write(&config_path, toml_content).map_err(|e| Error::Io(e))?;
+        std::fs::write(&config_path, content).map_err(Error::Io)?;
 
         Ok(())
     }
 
+    /// Parse config file content in the format named by `path`'s extension (TOML by default)
+    fn parse_config(content: &str, path: &Path) -> std::result::Result<Self, String> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(content).map_err(|e| e.to_string()),
+            _ => toml::from_str(content).map_err(|e| e.message().to_string()),
+        }
+    }
+
+    /// Serialize in the format named by `path`'s extension (TOML by default)
+    fn serialize_config(&self, path: &Path) -> Result<String> {
+        Ok(match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => {
+                serde_yaml::to_string(self).context("Failed to serialize global config as YAML")?
+            }
+            Some("json") => serde_json::to_string_pretty(self)?,
+            _ => toml::to_string_pretty(self).map_err(Error::TomlSer)?,
+        })
+    }
+
+    /// `path` if it exists, otherwise the first existing file with the same name in another
+    /// supported format, falling back to `path`
+    fn existing_config_file(path: PathBuf) -> PathBuf {
+        if path.exists() {
+            return path;
+        }
+        CONFIG_FORMATS
+            .iter()
+            .map(|ext| path.with_extension(ext))
+            .find(|candidate| candidate.is_file())
+            .unwrap_or(path)
+    }
+
     pub fn get_config_path() -> PathBuf {
         const APP_NAME: &str = env!("CARGO_PKG_NAME");
 
@@ -128,62 +366,138 @@ impl GlobalConfig {
 
         // Try to use XDG config directory, fall back to ~/.config
         if let Ok(xdg_config) = std::env::var("XDG_CONFIG_HOME") {
-            PathBuf::from(xdg_config)
-                .join(APP_NAME)
-                .join(config_file_name)
+            Self::existing_config_file(
+                PathBuf::from(xdg_config)
+                    .join(APP_NAME)
+                    .join(config_file_name),
+            )
         } else if let Ok(home) = std::env::var("HOME") {
-            PathBuf::from(home)
-                .join(".config")
-                .join(APP_NAME)
-                .join(config_file_name)
+            Self::existing_config_file(
+                PathBuf::from(home)
+                    .join(".config")
+                    .join(APP_NAME)
+                    .join(config_file_name),
+            )
         } else {
             PathBuf::from(".config")
         }
     }
 
+    /// Root directory for cached remote manifests
+    pub fn get_cache_dir() -> PathBuf {
+        Self::cache_dir_from(env::var("XDG_CACHE_HOME").ok(), env::var("HOME").ok())
+    }
+
+    fn cache_dir_from(xdg_cache: Option<String>, home: Option<String>) -> PathBuf {
+        const APP_NAME: &str = env!("CARGO_PKG_NAME");
+
+        // Follow XDG, falling back to ~/.cache, then the current directory
+        if let Some(xdg_cache) = xdg_cache.filter(|v| !v.is_empty()) {
+            PathBuf::from(xdg_cache).join(APP_NAME)
+        } else if let Some(home) = home.filter(|v| !v.is_empty()) {
+            PathBuf::from(home).join(".cache").join(APP_NAME)
+        } else {
+            PathBuf::from(".manifest-cache")
+        }
+    }
+
     fn get_config_file_name() -> String {
-        // Use the metadata embedded at build time
+        Self::embedded_defaults().config_file_name
+    }
+
+    /// Default settings embedded from Cargo.toml metadata at build time
+    pub fn embedded_defaults() -> DefaultSettings {
         let metadata_json = env!("PACKAGE_METADATA_JSON");
-        let metadata: DefaultSettings = serde_json::from_str(metadata_json)
-            .expect("Failed to parse embedded package metadata for config file name");
-        metadata.config_file_name
+        serde_json::from_str(metadata_json).expect("Failed to parse embedded package metadata")
     }
 
-    pub fn get_tools_directory(&self) -> &PathBuf {
-        &self.tools_dir
+    /// Absolute tools directory, independent of the current working directory
+    pub fn get_tools_directory(&self) -> PathBuf {
+        Self::resolve_tools_dir(&self.tools_dir, &Self::get_config_path())
     }
 
-    pub fn find_tool_manifest(&self, tool_name: &str) -> Result<Option<PathBuf>> {
-        for source in &self.manifest_sources {
+    fn resolve_tools_dir(tools_dir: &Path, config_path: &Path) -> PathBuf {
+        if tools_dir.is_absolute() {
+            return tools_dir.to_path_buf();
+        }
+        let base = config_path.parent().unwrap_or(Path::new(""));
+        std::path::absolute(base.join(tools_dir)).unwrap_or_else(|_| base.join(tools_dir))
+    }
+
+    /// Where `run --detach` logs and PID files go when `output_dir` isn't set
+    pub fn detached_log_dir(tool_name: &str) -> PathBuf {
+        Self::get_cache_dir().join("runs").join(tool_name)
+    }
+
+    /// Where a phase's step logs go when `output_dir` is set
+    pub fn step_log_dir(&self, tool_name: &str, phase: &str) -> Option<PathBuf> {
+        self.output_dir
+            .as_ref()
+            .map(|dir| dir.join(tool_name).join(phase))
+    }
+
+    /// The first manifest for `tool_name` among the sources, and which source it came from
+    pub fn find_tool_manifest(&self, tool_name: &str) -> Result<Option<ResolvedManifest<'_>>> {
+        crate::config::validate_tool_name(tool_name)?;
+        for (index, source) in self.unique_sources() {
+            let found = |path: PathBuf| {
+                Ok(Some(ResolvedManifest {
+                    path,
+                    source,
+                    index,
+                }))
+            };
             match source.source_type.as_str() {
                 "local" => {
-                    let manifest_path =
-                        PathBuf::from(&source.path).join(format!("{}.jsonc", tool_name));
+                    let manifest_path = crate::config::Config::manifest_path(
+                        &source.join_subdir(PathBuf::from(&source.path)),
+                        tool_name,
+                        &self.manifest_formats,
+                    );
                     if manifest_path.exists() {
-                        return Ok(Some(manifest_path));
+                        return found(manifest_path);
                     }
                 }
                 "git" => {
-                    // For git sources, check if already cloned locally
-                    let local_path = PathBuf::from(".manifest-cache")
-                        .join(Self::sanitize_url(&source.path))
-                        .join(format!("{}.jsonc", tool_name));
+                    // Lookups only read existing clones; `manifests update` fetches new ones
+                    let cache_dir = Self::git_clone_dir(source);
+                    if !cache_dir.exists() {
+                        continue;
+                    }
+                    if self.refreshes(source) {
+                        Self::refresh_git_clone(source, &self.git_client(), &cache_dir);
+                    }
+                    let local_path = crate::config::Config::manifest_path(
+                        &source.join_subdir(cache_dir),
+                        tool_name,
+                        &self.manifest_formats,
+                    );
                     if local_path.exists() {
-                        return Ok(Some(local_path));
+                        return found(local_path);
                     }
                 }
                 "url" => {
-                    // For URL sources, check cached version
-                    let local_path = PathBuf::from(".manifest-cache")
-                        .join("url-manifests")
-                        .join(format!("{}.jsonc", tool_name));
-                    if local_path.exists() {
-                        return Ok(Some(local_path));
+                    // For URL sources, download into the cache (falling back to a cached copy)
+                    let cache_dir = Self::url_manifest_dir(source);
+                    let token = crate::credentials::source_token(source);
+                    if let Some(local_path) = crate::remote::fetch_url_manifest(
+                        &source.path,
+                        tool_name,
+                        &cache_dir,
+                        &self.manifest_formats,
+                        self.refreshes(source),
+                        token.as_deref(),
+                        &self.network_policy(),
+                    )? {
+                        return found(local_path);
                     }
                 }
                 _ => {
-                    println!(
-                        "Warning: Unknown manifest source type: {}",
+                    eprintln!(
+                        "{} Ignoring manifest source {} ({}): unknown source type '{}'",
+                        marker(Marker::Warn),
+                        index + 1,
+                        source.path,
                         source.source_type
                     );
                 }
@@ -192,30 +506,312 @@ impl GlobalConfig {
         Ok(None)
     }
 
-    fn sanitize_url(url: &str) -> String {
-        url.replace(['/', ':', '.'], "_")
+    /// Whether `source` should be refreshed, honoring the per-invocation override
+    pub fn refreshes(&self, source: &ManifestSource) -> bool {
+        self.update_sources.unwrap_or(source.auto_update)
     }
 
-    pub fn add_manifest_source(
-        &mut self,
-        source_type: String,
-        path: String,
-        branch: Option<String>,
-        auto_update: bool,
+    /// Sources (with their index) in search order, skipping any that resolve to a location
+    /// already visited so nested or duplicate sources are only traversed once
+    pub fn unique_sources(&self) -> Vec<(usize, &ManifestSource)> {
+        self.partition_sources().0
+    }
+
+    /// Warn about each source `unique_sources` skips
+    fn warn_duplicate_sources(&self) {
+        for (index, source) in self.partition_sources().1 {
+            eprintln!(
+                "{} Skipping manifest source {} ({}): already visited",
+                marker(Marker::Warn),
+                index + 1,
+                source.path
+            );
+        }
+    }
+
+    /// Sources (with their index) split into the first to reach each location and the rest
+    fn partition_sources(&self) -> (IndexedSources<'_>, IndexedSources<'_>) {
+        let mut visited = HashSet::new();
+        self.manifest_sources
+            .iter()
+            .enumerate()
+            .partition(|(_, source)| visited.insert(Self::source_location(source)))
+    }
+
+    /// Location a source resolves to, normalized so different spellings compare equal
+    fn source_location(source: &ManifestSource) -> PathBuf {
+        match source.source_type.as_str() {
+            "url" => PathBuf::from(source.path.trim_end_matches('/')),
+            _ => match Self::source_manifest_dir(source) {
+                Some(dir) => dir.canonicalize().unwrap_or(dir),
+                None => PathBuf::from(&source.path),
+            },
+        }
+    }
+
+    /// Make sure `git` can run before a git source has to be cloned or pulled
+    fn require_git(source: &ManifestSource) -> Result<()> {
+        let failure = match std::process::Command::new("git").arg("--version").output() {
+            Ok(output) if output.status.success() => return Ok(()),
+            Ok(output) => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            Err(e) => e.to_string(),
+        };
+
+        Err(Error::Config(format!(
+            "git is required to fetch manifest source {}, but `git --version` failed: {}. \
+             Install git or remove the source from the app configuration.",
+            source.path, failure
+        )))
+    }
+
+    /// Tool names a url source lists in its `index.json`, or `None` if it publishes no index.
+    /// The cached index is used as-is unless `refresh` is set.
+    pub fn fetch_source_index(
+        source: &ManifestSource,
+        refresh: bool,
+        policy: &NetworkPolicy,
+    ) -> Result<Option<Vec<String>>> {
+        let cached = Self::get_cache_dir()
+            .join("url-indexes")
+            .join(format!("{}.json", Self::sanitize_url(&source.path)));
+        let token = crate::credentials::source_token(source);
+        crate::remote::fetch_url_index(&source.path, &cached, refresh, token.as_deref(), policy)
+    }
+
+    /// Directory `[[workspace.dir]]` expands to for this invocation
+    pub fn workspace_dir(&self) -> Result<PathBuf> {
+        let cwd = std::env::current_dir().context("Failed to read the current directory")?;
+        Ok(crate::config::workspace_dir(&cwd, self.workspace_vcs_root))
+    }
+
+    /// Timeouts and retries for fetching remote sources
+    pub fn network_policy(&self) -> NetworkPolicy {
+        NetworkPolicy {
+            timeout: std::time::Duration::from_secs(self.network_timeout_secs),
+            retries: self.network_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Git client honoring the configured network policy
+    pub fn git_client(&self) -> crate::remote::SystemGit {
+        crate::remote::SystemGit {
+            policy: self.network_policy(),
+            ssh_command: self.git_ssh_command.clone(),
+        }
+    }
+
+    /// Remote source probe honoring the configured network policy
+    pub fn remote_probe(&self) -> crate::remote::SystemProbe {
+        crate::remote::SystemProbe {
+            policy: self.network_policy(),
+            ssh_command: self.git_ssh_command.clone(),
+        }
+    }
+
+    /// Fetch the latest manifests of a remote source: pull (or clone) a git source,
+    /// re-download a url source's index and the cached manifests it lists. Returns a
+    /// short note on what was fetched.
+    pub fn update_source(
+        source: &ManifestSource,
+        git: &dyn crate::remote::GitClient,
+        policy: &NetworkPolicy,
+        formats: &[String],
     ) -> Result<String> {
-        // Validate source type
-        match source_type.as_str() {
-            "local" | "git" | "url" => {}
-            _ => {
-                return Err(crate::error::Error::Config(format!(
-                    "Invalid source type '{}'. Must be one of: local, git, url",
-                    source_type
-                )));
+        match source.source_type.as_str() {
+            "git" => {
+                let dest = Self::git_clone_dir(source);
+                if dest.exists() {
+                    Self::require_git(source)?;
+                    git.pull(&dest)?;
+                    Ok(format!("pulled {}", dest.display()))
+                } else {
+                    Self::clone_git_source_into(source, git, &dest)?;
+                    Ok(format!("cloned into {}", dest.display()))
+                }
             }
+            "url" => {
+                let Some(tools) = Self::fetch_source_index(source, true, policy)? else {
+                    return Ok("no index.json published; tools are fetched on demand".to_string());
+                };
+
+                // Only manifests already cached are refreshed; the rest are fetched on demand
+                let cache_dir = Self::url_manifest_dir(source);
+                let token = crate::credentials::source_token(source);
+                let mut refreshed = 0;
+                for tool in &tools {
+                    let cached = crate::config::validate_tool_name(tool).is_ok()
+                        && formats
+                            .iter()
+                            .any(|ext| cache_dir.join(format!("{}.{}", tool, ext)).is_file());
+                    if cached
+                        && crate::remote::fetch_url_manifest(
+                            &source.path,
+                            tool,
+                            &cache_dir,
+                            formats,
+                            true,
+                            token.as_deref(),
+                            policy,
+                        )?
+                        .is_some()
+                    {
+                        refreshed += 1;
+                    }
+                }
+                Ok(format!(
+                    "index lists {} tool(s), refreshed {} cached manifest(s)",
+                    tools.len(),
+                    refreshed
+                ))
+            }
+            other => Err(Error::Config(format!(
+                "Source type '{}' has nothing to fetch",
+                other
+            ))),
+        }
+    }
+
+    /// Local directory holding a source's manifests (the cache for remote sources)
+    pub fn source_manifest_dir(source: &ManifestSource) -> Option<PathBuf> {
+        match source.source_type.as_str() {
+            "local" => Some(source.join_subdir(PathBuf::from(&source.path))),
+            "git" => Some(source.join_subdir(Self::git_clone_dir(source))),
+            "url" => Some(Self::url_manifest_dir(source)),
+            _ => None,
+        }
+    }
+
+    /// Cache directory holding the manifests downloaded from a url source, one per source
+    /// so sources publishing the same tool don't overwrite each other's copy
+    pub fn url_manifest_dir(source: &ManifestSource) -> PathBuf {
+        Self::get_cache_dir()
+            .join("url-manifests")
+            .join(Self::sanitize_url(&source.path))
+    }
+
+    /// Cache directory a git source is cloned into
+    pub fn git_clone_dir(source: &ManifestSource) -> PathBuf {
+        Self::get_cache_dir().join(Self::sanitize_url(&source.path))
+    }
+
+    /// Clone a git source into its cache directory unless it's already there
+    pub fn clone_git_source(
+        source: &ManifestSource,
+        git: &dyn crate::remote::GitClient,
+    ) -> Result<PathBuf> {
+        let dest = Self::git_clone_dir(source);
+        Self::clone_git_source_into(source, git, &dest)?;
+        Ok(dest)
+    }
+
+    fn clone_git_source_into(
+        source: &ManifestSource,
+        git: &dyn crate::remote::GitClient,
+        dest: &Path,
+    ) -> Result<()> {
+        if dest.exists() {
+            return Ok(());
+        }
+
+        Self::require_git(source)?;
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create cache directory: {}", parent.display())
+            })?;
+        }
+        git.clone_repo(&source.path, source.branch.as_deref(), dest)
+    }
+
+    /// Pull an existing clone of a git source. A failed pull only warns, so lookups keep
+    /// working from the cached clone while the remote is unreachable.
+    fn refresh_git_clone(source: &ManifestSource, git: &dyn crate::remote::GitClient, dest: &Path) {
+        if let Err(e) = Self::require_git(source).and_then(|()| git.pull(dest)) {
+            eprintln!(
+                "{} Could not update manifest source {}: {}; using the cached clone",
+                marker(Marker::Warn),
+                source.path,
+                e
+            );
+        }
+    }
+
+    /// Directory holding the last recorded copy of a source's manifests
+    pub fn source_snapshot_dir(source: &ManifestSource) -> PathBuf {
+        Self::get_cache_dir()
+            .join("snapshots")
+            .join(Self::sanitize_url(&source.path))
+    }
+
+    /// Find a source by its 1-based position (as shown by `manifests list`) or its path
+    pub fn find_source(&self, selector: &str) -> Result<&ManifestSource> {
+        let position = self.source_position(selector)?;
+        Ok(&self.manifest_sources[position])
+    }
+
+    fn source_position(&self, selector: &str) -> Result<usize> {
+        if let Ok(index) = selector.parse::<usize>() {
+            return index
+                .checked_sub(1)
+                .filter(|i| *i < self.manifest_sources.len())
+                .ok_or_else(|| Error::Config(format!("No manifest source at index {}", index)));
+        }
+
+        self.manifest_sources
+            .iter()
+            .position(|source| source.path == selector)
+            .ok_or_else(|| Error::Config(format!("No manifest source matching: {}", selector)))
+    }
+
+    /// Change the branch of a git source (selected by 1-based index or path)
+    pub fn set_source_branch(&mut self, selector: &str, branch: String) -> Result<()> {
+        let position = self.source_position(selector)?;
+        let source = &mut self.manifest_sources[position];
+        if source.source_type != "git" {
+            return Err(Error::Config(format!(
+                "Only git sources have a branch; source {} is {}",
+                position + 1,
+                source.source_type
+            )));
+        }
+
+        source.branch = Some(branch);
+        Ok(())
+    }
+
+    /// Point a source (selected by 1-based index or path) at a new path, validated for its type
+    pub fn set_source_path(&mut self, selector: &str, path: String) -> Result<String> {
+        let position = self.source_position(selector)?;
+        let source_type = self.manifest_sources[position].source_type.clone();
+        let validated_path = Self::validate_source_path(&source_type, path)?;
+
+        let duplicate = self.manifest_sources.iter().enumerate().any(|(i, source)| {
+            i != position && source.source_type == source_type && source.path == validated_path
+        });
+        if duplicate {
+            return Err(Error::Config(format!(
+                "Manifest source already exists: {} {}",
+                source_type, validated_path
+            )));
+        }
+
+        self.manifest_sources[position].path = validated_path.clone();
+        Ok(validated_path)
+    }
+
+    /// Check a source path for its type, resolving local paths to absolute ones
+    fn validate_source_path(source_type: &str, path: String) -> Result<String> {
+        // Validate source type
+        if !SOURCE_TYPES.contains(&source_type) {
+            return Err(crate::error::Error::Config(format!(
+                "Invalid source type '{}'. Must be one of: {}",
+                source_type,
+                SOURCE_TYPES.join(", ")
+            )));
         }
 
         // Validate and resolve path based on source type
-        let validated_path = match source_type.as_str() {
+        let validated_path = match source_type {
             "local" => {
                 // Resolve to absolute path
                 let path_buf = PathBuf::from(&path);
@@ -254,20 +850,27 @@ impl GlobalConfig {
                 // For git URLs, do basic validation
                 if !path.starts_with("http://")
                     && !path.starts_with("https://")
-                    && !path.starts_with("git@")
+                    && !crate::remote::is_ssh_url(&path)
                 {
                     return Err(crate::error::Error::Config(format!(
-                        "Git source must be a valid git URL (http://, https://, or git@): {}",
+                        "Git source must be a valid git URL (http://, https://, ssh:// or user@host:path): {}",
                         path
                     )));
                 }
-                path
+                Self::normalize_git_url(&path)
             }
             "url" => {
                 // For URLs, do basic validation
-                if !path.starts_with("http://") && !path.starts_with("https://") {
+                if let Some(file_path) = path.strip_prefix("file://") {
+                    if !Path::new(file_path).is_dir() {
+                        return Err(crate::error::Error::Config(format!(
+                            "file:// URL source must point to an existing directory: {}",
+                            path
+                        )));
+                    }
+                } else if !path.starts_with("http://") && !path.starts_with("https://") {
                     return Err(crate::error::Error::Config(format!(
-                        "URL source must be a valid HTTP/HTTPS URL: {}",
+                        "URL source must be a valid HTTP/HTTPS URL or file:// path: {}",
                         path
                     )));
                 }
@@ -276,6 +879,62 @@ impl GlobalConfig {
             _ => unreachable!(), // Already validated above
         };
 
+        Ok(validated_path)
+    }
+
+    /// Canonical spelling of a git URL so equivalent forms share a cache directory:
+    /// trailing slashes are dropped and known hosts get a `.git` suffix. SSH `git@`
+    /// URLs are kept as written.
+    fn normalize_git_url(url: &str) -> String {
+        const KNOWN_HOSTS: [&str; 4] =
+            ["github.com", "gitlab.com", "bitbucket.org", "codeberg.org"];
+
+        if crate::remote::is_ssh_url(url) {
+            return url.to_string();
+        }
+
+        let url = url.trim_end_matches('/');
+        let host = url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.split('/').next())
+            .unwrap_or_default();
+        if KNOWN_HOSTS.contains(&host.to_ascii_lowercase().as_str()) && !url.ends_with(".git") {
+            format!("{}.git", url)
+        } else {
+            url.to_string()
+        }
+    }
+
+    fn sanitize_url(url: &str) -> String {
+        url.replace(['/', ':', '.'], "_")
+    }
+
+    pub fn add_manifest_source(
+        &mut self,
+        source_type: String,
+        path: String,
+        branch: Option<String>,
+        subdir: Option<String>,
+        auto_update: bool,
+        auth: Option<SourceAuth>,
+    ) -> Result<String> {
+        let validated_path = Self::validate_source_path(&source_type, path)?;
+
+        // Subdirectories must stay inside the source
+        if let Some(subdir) = &subdir {
+            let subdir_path = Path::new(subdir);
+            if subdir_path.is_absolute()
+                || subdir_path
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                return Err(crate::error::Error::Config(format!(
+                    "Manifest subdir must be a relative path inside the source: {}",
+                    subdir
+                )));
+            }
+        }
+
         // Check if source already exists (using the validated path)
         let source_exists = self
             .manifest_sources
@@ -294,7 +953,9 @@ impl GlobalConfig {
             source_type,
             path: validated_path.clone(),
             branch,
+            subdir,
             auto_update,
+            auth,
         };
 
         self.manifest_sources.push(new_source);
@@ -310,8 +971,11 @@ impl GlobalConfig {
         let package_name = env!("CARGO_PKG_NAME");
 
         // Parse app_config_path
-        let resolved_config_dir =
-            Self::resolve_config_path(&metadata.app_config_path, package_name)?;
+        let resolved_config_dir = Self::resolve_config_path(
+            &metadata.app_config_path,
+            package_name,
+            Self::is_writable_dir,
+        )?;
 
         // Parse manifests_dir
         let manifests_dir = Self::resolve_template_path(
@@ -328,13 +992,29 @@ impl GlobalConfig {
                 source_type: "local".to_string(),
                 path: manifests_dir.to_string_lossy().to_string(),
                 branch: None,
+                subdir: None,
+                auth: None,
                 auto_update: false,
             }],
             tools_dir,
             default_manifest_dir: manifests_dir,
+            max_concurrency: default_max_concurrency(),
+            spawn_mode: SpawnMode::default(),
+            manifest_formats: default_manifest_formats(),
+            output_dir: None,
+            network_timeout_secs: default_network_timeout_secs(),
+            network_retries: default_network_retries(),
+            git_ssh_command: None,
+            workspace_vcs_root: false,
+            update_sources: None,
         })
     }
-    fn resolve_config_path(paths: &[String], package_name: &str) -> Result<PathBuf> {
+    /// First candidate directory `writable` accepts, or the current directory
+    fn resolve_config_path(
+        paths: &[String],
+        package_name: &str,
+        writable: impl Fn(&Path) -> bool,
+    ) -> Result<PathBuf> {
         for path_template in paths {
             let resolved_path = Self::expand_env_vars(path_template, package_name)?;
 
@@ -345,12 +1025,8 @@ impl GlobalConfig {
 
             let path = PathBuf::from(resolved_path);
 
-            // Check if this path is usable (parent directories exist or can be created)
-            if let Some(parent) = path.parent() {
-                if parent.exists() || std::fs::create_dir_all(parent).is_ok() {
-                    return Ok(path);
-                }
-            } else if path.exists() {
+            // Only accept a directory the config file can actually be written to
+            if writable(&path) {
                 return Ok(path);
             }
         }
@@ -358,6 +1034,23 @@ impl GlobalConfig {
         // If no paths work, fallback to current directory
         Ok(PathBuf::from("./"))
     }
+
+    /// Whether `dir` exists or can be created, and a file can be created inside it
+    fn is_writable_dir(dir: &Path) -> bool {
+        if std::fs::create_dir_all(dir).is_err() {
+            return false;
+        }
+
+        let probe = dir.join(format!(
+            ".{}-write-probe-{}",
+            env!("CARGO_PKG_NAME"),
+            std::process::id()
+        ));
+        let writable = std::fs::File::create(&probe).is_ok();
+        let _ = std::fs::remove_file(&probe);
+        writable
+    }
+
     fn resolve_tools_path(paths: &[String]) -> Result<PathBuf> {
         for path_template in paths {
             let resolved_path = Self::expand_env_vars(path_template, "")?;
@@ -419,10 +1112,10 @@ impl GlobalConfig {
 
         // If we still have unexpanded variables, it means they're not set
         // For $XDG_DATA_HOME, fallback to $HOME/.local/share if $HOME is available
-        if result.contains("$XDG_DATA_HOME") {
-            if let Ok(home) = env::var("HOME") {
-                result = result.replace("$XDG_DATA_HOME", &format!("{}/.local/share", home));
-            }
+        if result.contains("$XDG_DATA_HOME")
+            && let Ok(home) = env::var("HOME")
+        {
+            result = result.replace("$XDG_DATA_HOME", &format!("{}/.local/share", home));
         }
 
         // If we still have unexpanded variables, it means they're not available
@@ -470,7 +1163,9 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let paths = vec![temp_dir.path().to_string_lossy().to_string()];
 
-        let result = GlobalConfig::resolve_config_path(&paths, "test-app").unwrap();
+        let result =
+            GlobalConfig::resolve_config_path(&paths, "test-app", GlobalConfig::is_writable_dir)
+                .unwrap();
         assert_eq!(result, temp_dir.path());
     }
 
@@ -478,10 +1173,106 @@ mod tests {
     fn test_resolve_config_path_fallback() {
         let paths = vec!["$NONEXISTENT_VAR/config".to_string()];
 
-        let result = GlobalConfig::resolve_config_path(&paths, "test-app").unwrap();
+        let result =
+            GlobalConfig::resolve_config_path(&paths, "test-app", GlobalConfig::is_writable_dir)
+                .unwrap();
+        assert_eq!(result, PathBuf::from("./"));
+    }
+
+    #[test]
+    fn test_resolve_config_path_skips_unusable_candidates() {
+        let temp_dir = tempdir().unwrap();
+        let not_a_dir = temp_dir.path().join("file");
+        fs::write(&not_a_dir, "").unwrap();
+        let usable = temp_dir.path().join("usable/config");
+        let paths = vec![
+            not_a_dir.join("config").to_string_lossy().to_string(),
+            usable.to_string_lossy().to_string(),
+        ];
+
+        let result =
+            GlobalConfig::resolve_config_path(&paths, "test-app", GlobalConfig::is_writable_dir)
+                .unwrap();
+        assert_eq!(result, usable);
+        assert_eq!(fs::read_dir(&usable).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_resolve_config_path_skips_read_only_parent() {
+        let read_only = PathBuf::from("/read-only/config");
+        let writable = PathBuf::from("/writable/config");
+        let paths = vec![
+            read_only.to_string_lossy().to_string(),
+            writable.to_string_lossy().to_string(),
+        ];
+
+        // Probe instead of relying on permission bits, which don't stop root
+        let probed = std::cell::RefCell::new(Vec::new());
+        let result = GlobalConfig::resolve_config_path(&paths, "test-app", |dir| {
+            probed.borrow_mut().push(dir.to_path_buf());
+            dir == writable
+        })
+        .unwrap();
+        assert_eq!(result, writable);
+        assert_eq!(
+            probed.into_inner(),
+            vec![read_only.clone(), writable.clone()]
+        );
+
+        let result = GlobalConfig::resolve_config_path(&paths[..1], "test-app", |_| false).unwrap();
         assert_eq!(result, PathBuf::from("./"));
     }
 
+    #[test]
+    fn test_yaml_global_config_round_trips() {
+        let path = Path::new("/config/tool-meister/config.yaml");
+        let yaml = r#"
+manifest_sources:
+  - type: local
+    path: /srv/manifests
+tools_dir: tools
+default_manifest_dir: /srv/manifests
+max_concurrency: 3
+"#;
+
+        let config = GlobalConfig::parse_config(yaml, path).unwrap();
+        assert_eq!(config.max_concurrency, 3);
+        assert_eq!(config.manifest_sources[0].path, "/srv/manifests");
+
+        let written = config.serialize_config(path).unwrap();
+        assert!(written.contains("max_concurrency: 3"), "{}", written);
+        let reloaded = GlobalConfig::parse_config(&written, path).unwrap();
+        assert_eq!(reloaded.tools_dir, PathBuf::from("tools"));
+        assert_eq!(reloaded.manifest_sources[0].source_type, "local");
+
+        let json = config.serialize_config(Path::new("config.json")).unwrap();
+        let from_json = GlobalConfig::parse_config(&json, Path::new("config.json")).unwrap();
+        assert_eq!(from_json.max_concurrency, 3);
+        assert!(GlobalConfig::parse_config(yaml, Path::new("config.toml")).is_err());
+    }
+
+    #[test]
+    fn test_existing_config_file_prefers_configured_format() {
+        let temp_dir = tempdir().unwrap();
+        let toml_path = temp_dir.path().join("config.toml");
+        assert_eq!(
+            GlobalConfig::existing_config_file(toml_path.clone()),
+            toml_path
+        );
+
+        fs::write(temp_dir.path().join("config.yaml"), "").unwrap();
+        assert_eq!(
+            GlobalConfig::existing_config_file(toml_path.clone()),
+            temp_dir.path().join("config.yaml")
+        );
+
+        fs::write(&toml_path, "").unwrap();
+        assert_eq!(
+            GlobalConfig::existing_config_file(toml_path.clone()),
+            toml_path
+        );
+    }
+
     #[test]
     fn test_resolve_tools_path_with_valid_path() {
         let temp_dir = tempdir().unwrap();
@@ -509,7 +1300,9 @@ mod tests {
             "local".to_string(),
             temp_dir.path().to_string_lossy().to_string(),
             None,
+            None,
             true,
+            None,
         );
 
         assert!(result.is_ok());
@@ -525,7 +1318,9 @@ mod tests {
             "local".to_string(),
             "/nonexistent/path".to_string(),
             None,
+            None,
             true,
+            None,
         );
 
         assert!(result.is_err());
@@ -543,7 +1338,9 @@ mod tests {
             "local".to_string(),
             file_path.to_string_lossy().to_string(),
             None,
+            None,
             true,
+            None,
         );
 
         assert!(result.is_err());
@@ -562,7 +1359,9 @@ mod tests {
             "git".to_string(),
             "https://github.com/example/repo.git".to_string(),
             Some("main".to_string()),
+            None,
             true,
+            None,
         );
 
         assert!(result.is_ok());
@@ -574,8 +1373,14 @@ mod tests {
     #[test]
     fn test_add_manifest_source_git_invalid_url() {
         let mut config = GlobalConfig::default();
-        let result =
-            config.add_manifest_source("git".to_string(), "invalid-url".to_string(), None, true);
+        let result = config.add_manifest_source(
+            "git".to_string(),
+            "invalid-url".to_string(),
+            None,
+            None,
+            true,
+            None,
+        );
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("valid git URL"));
@@ -588,7 +1393,9 @@ mod tests {
             "url".to_string(),
             "https://example.com/manifests".to_string(),
             None,
+            None,
             false,
+            None,
         );
 
         assert!(result.is_ok());
@@ -604,7 +1411,9 @@ mod tests {
             "url".to_string(),
             "ftp://example.com/manifests".to_string(),
             None,
+            None,
             true,
+            None,
         );
 
         assert!(result.is_err());
@@ -614,8 +1423,14 @@ mod tests {
     #[test]
     fn test_add_manifest_source_invalid_type() {
         let mut config = GlobalConfig::default();
-        let result =
-            config.add_manifest_source("invalid".to_string(), "/some/path".to_string(), None, true);
+        let result = config.add_manifest_source(
+            "invalid".to_string(),
+            "/some/path".to_string(),
+            None,
+            None,
+            true,
+            None,
+        );
 
         assert!(result.is_err());
         assert!(
@@ -635,15 +1450,46 @@ mod tests {
         let mut config = GlobalConfig::default();
 
         // Add source first time
-        let result1 = config.add_manifest_source("local".to_string(), path.clone(), None, true);
+        let result1 =
+            config.add_manifest_source("local".to_string(), path.clone(), None, None, true, None);
         assert!(result1.is_ok());
 
         // Try to add same source again
-        let result2 = config.add_manifest_source("local".to_string(), path, None, true);
+        let result2 = config.add_manifest_source("local".to_string(), path, None, None, true, None);
         assert!(result2.is_err());
         assert!(result2.unwrap_err().to_string().contains("already exists"));
     }
 
+    #[test]
+    fn test_locked_env_value() {
+        assert!(locked_env_value(Some("1")));
+        assert!(locked_env_value(Some("true")));
+        assert!(!locked_env_value(Some("0")));
+        assert!(!locked_env_value(Some("false")));
+        assert!(!locked_env_value(Some("")));
+        assert!(!locked_env_value(None));
+    }
+
+    #[test]
+    fn test_url_sources_cache_manifests_separately() {
+        let source = |path: &str| ManifestSource {
+            source_type: "url".to_string(),
+            path: path.to_string(),
+            branch: None,
+            subdir: None,
+            auto_update: true,
+            auth: None,
+        };
+
+        let first = GlobalConfig::url_manifest_dir(&source("https://one.example.com/manifests"));
+        let second = GlobalConfig::url_manifest_dir(&source("https://two.example.com/manifests"));
+        assert_ne!(first, second);
+        assert_eq!(
+            GlobalConfig::source_manifest_dir(&source("https://one.example.com/manifests")),
+            Some(first)
+        );
+    }
+
     #[test]
     fn test_sanitize_url() {
         let result = GlobalConfig::sanitize_url("https://github.com/user/repo.git");
@@ -659,17 +1505,24 @@ mod tests {
         let manifest_content = r#"{"repo": {"name": "test"}, "actions": {}}"#;
         fs::write(manifest_dir.join("test-tool.jsonc"), manifest_content).unwrap();
 
-        let mut config = GlobalConfig::default();
-        config.manifest_sources = vec![ManifestSource {
-            source_type: "local".to_string(),
-            path: manifest_dir.to_string_lossy().to_string(),
-            branch: None,
-            auto_update: false,
-        }];
+        let config = GlobalConfig {
+            manifest_sources: vec![ManifestSource {
+                source_type: "local".to_string(),
+                path: manifest_dir.to_string_lossy().to_string(),
+                branch: None,
+                subdir: None,
+                auth: None,
+                auto_update: false,
+            }],
+            ..Default::default()
+        };
 
-        let result = config.find_tool_manifest("test-tool").unwrap();
-        assert!(result.is_some());
-        assert!(result.unwrap().ends_with("test-tool.jsonc"));
+        let result = config.find_tool_manifest("test-tool").unwrap().unwrap();
+        assert!(result.path.ends_with("test-tool.jsonc"));
+        assert_eq!(
+            result.provenance(),
+            format!("local:{} (source 1)", manifest_dir.display())
+        );
     }
 
     #[test]
@@ -678,15 +1531,469 @@ mod tests {
         let manifest_dir = temp_dir.path().join("manifests");
         fs::create_dir_all(&manifest_dir).unwrap();
 
+        let config = GlobalConfig {
+            manifest_sources: vec![ManifestSource {
+                source_type: "local".to_string(),
+                path: manifest_dir.to_string_lossy().to_string(),
+                branch: None,
+                subdir: None,
+                auth: None,
+                auto_update: false,
+            }],
+            ..Default::default()
+        };
+
+        let result = config
+            .find_tool_manifest("nonexistent-tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_find_tool_manifest_local_with_subdir() {
+        let temp_dir = tempdir().unwrap();
+        let tools_dir = temp_dir.path().join("tools");
+        fs::create_dir_all(&tools_dir).unwrap();
+
+        let manifest_content = r#"{"repo": {"name": "test"}, "actions": {}}"#;
+        fs::write(tools_dir.join("test-tool.jsonc"), manifest_content).unwrap();
+
+        let config = GlobalConfig {
+            manifest_sources: vec![ManifestSource {
+                source_type: "local".to_string(),
+                path: temp_dir.path().to_string_lossy().to_string(),
+                branch: None,
+                subdir: Some("tools".to_string()),
+                auth: None,
+                auto_update: false,
+            }],
+            ..Default::default()
+        };
+
+        let result = config
+            .find_tool_manifest("test-tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert_eq!(result, Some(tools_dir.join("test-tool.jsonc")));
+    }
+
+    #[test]
+    fn test_add_manifest_source_subdir_traversal() {
+        let temp_dir = tempdir().unwrap();
+
+        let mut config = GlobalConfig::default();
+        let result = config.add_manifest_source(
+            "local".to_string(),
+            temp_dir.path().to_string_lossy().to_string(),
+            None,
+            Some("../outside".to_string()),
+            true,
+            None,
+        );
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("subdir"));
+    }
+
+    #[test]
+    fn test_cache_dir_follows_xdg_cache_home() {
+        let result = GlobalConfig::cache_dir_from(
+            Some("/custom/cache".to_string()),
+            Some("/home/testuser".to_string()),
+        );
+        assert_eq!(result, PathBuf::from("/custom/cache/tool-meister"));
+    }
+
+    #[test]
+    fn test_cache_dir_falls_back_to_home_cache() {
+        let result = GlobalConfig::cache_dir_from(None, Some("/home/testuser".to_string()));
+        assert_eq!(result, PathBuf::from("/home/testuser/.cache/tool-meister"));
+
+        let result =
+            GlobalConfig::cache_dir_from(Some(String::new()), Some("/home/testuser".to_string()));
+        assert_eq!(result, PathBuf::from("/home/testuser/.cache/tool-meister"));
+    }
+
+    #[test]
+    fn test_add_manifest_source_file_url() {
+        let temp_dir = tempdir().unwrap();
+        let url = format!("file://{}", temp_dir.path().display());
+
         let mut config = GlobalConfig::default();
-        config.manifest_sources = vec![ManifestSource {
+        let result =
+            config.add_manifest_source("url".to_string(), url.clone(), None, None, false, None);
+        assert_eq!(result.unwrap(), url);
+
+        let missing = config.add_manifest_source(
+            "url".to_string(),
+            "file:///nonexistent/manifests".to_string(),
+            None,
+            None,
+            false,
+            None,
+        );
+        assert!(
+            missing
+                .unwrap_err()
+                .to_string()
+                .contains("existing directory")
+        );
+    }
+
+    #[test]
+    fn test_find_tool_manifest_skips_revisited_source() {
+        let temp_dir = tempdir().unwrap();
+        let nested_dir = temp_dir.path().join("nested");
+        fs::create_dir_all(&nested_dir).unwrap();
+
+        let manifest_content = r#"{"repo": {"name": "test"}, "actions": {}}"#;
+        fs::write(nested_dir.join("test-tool.jsonc"), manifest_content).unwrap();
+
+        // The second source is the first one's subdir spelled differently
+        let config = GlobalConfig {
+            manifest_sources: vec![
+                ManifestSource {
+                    source_type: "local".to_string(),
+                    path: temp_dir.path().to_string_lossy().to_string(),
+                    branch: None,
+                    subdir: Some("nested".to_string()),
+                    auth: None,
+                    auto_update: false,
+                },
+                ManifestSource {
+                    source_type: "local".to_string(),
+                    path: nested_dir.join(".").to_string_lossy().to_string(),
+                    branch: None,
+                    subdir: None,
+                    auth: None,
+                    auto_update: false,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let sources = config.unique_sources();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].0, 0);
+
+        let result = config
+            .find_tool_manifest("test-tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert_eq!(result, Some(nested_dir.join("test-tool.jsonc")));
+    }
+
+    #[test]
+    fn test_set_source_path_rejects_duplicate() {
+        let first = tempdir().unwrap();
+        let second = tempdir().unwrap();
+
+        let mut config = GlobalConfig {
+            manifest_sources: Vec::new(),
+            ..Default::default()
+        };
+        for dir in [&first, &second] {
+            config
+                .add_manifest_source(
+                    "local".to_string(),
+                    dir.path().to_string_lossy().to_string(),
+                    None,
+                    None,
+                    false,
+                    None,
+                )
+                .unwrap();
+        }
+
+        let first_path = config.manifest_sources[0].path.clone();
+        let result = config.set_source_path("2", first_path);
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+
+        let moved = tempdir().unwrap();
+        let validated = config
+            .set_source_path("2", moved.path().to_string_lossy().to_string())
+            .unwrap();
+        assert_eq!(config.manifest_sources[1].path, validated);
+    }
+
+    fn local_source(path: &Path) -> ManifestSource {
+        ManifestSource {
             source_type: "local".to_string(),
-            path: manifest_dir.to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
             branch: None,
+            subdir: None,
+            auth: None,
             auto_update: false,
-        }];
+        }
+    }
 
-        let result = config.find_tool_manifest("nonexistent-tool").unwrap();
-        assert!(result.is_none());
+    #[test]
+    fn test_find_tool_manifest_prefers_local_override() {
+        let temp_dir = tempdir().unwrap();
+        let manifest_content = r#"{"repo": {"name": "test"}, "actions": {}}"#;
+        fs::write(temp_dir.path().join("test-tool.jsonc"), manifest_content).unwrap();
+        fs::write(
+            temp_dir.path().join("test-tool.local.jsonc"),
+            manifest_content,
+        )
+        .unwrap();
+
+        let config = GlobalConfig {
+            manifest_sources: vec![local_source(temp_dir.path())],
+            ..Default::default()
+        };
+
+        let result = config
+            .find_tool_manifest("test-tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert_eq!(result, Some(temp_dir.path().join("test-tool.local.jsonc")));
+    }
+
+    #[test]
+    fn test_find_tool_manifest_uses_base_without_override() {
+        let temp_dir = tempdir().unwrap();
+        let manifest_content = r#"{"repo": {"name": "test"}, "actions": {}}"#;
+        fs::write(temp_dir.path().join("test-tool.jsonc"), manifest_content).unwrap();
+
+        let config = GlobalConfig {
+            manifest_sources: vec![local_source(temp_dir.path())],
+            ..Default::default()
+        };
+
+        let result = config
+            .find_tool_manifest("test-tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert_eq!(result, Some(temp_dir.path().join("test-tool.jsonc")));
+    }
+
+    #[test]
+    fn test_find_tool_manifest_follows_manifest_formats_order() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("test-tool.jsonc"), "{}").unwrap();
+        fs::write(temp_dir.path().join("test-tool.toml"), "").unwrap();
+
+        let mut config = GlobalConfig {
+            manifest_sources: vec![local_source(temp_dir.path())],
+            ..Default::default()
+        };
+        let result = config
+            .find_tool_manifest("test-tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert_eq!(result, Some(temp_dir.path().join("test-tool.jsonc")));
+
+        config.manifest_formats = vec!["toml".to_string(), "jsonc".to_string()];
+        let result = config
+            .find_tool_manifest("test-tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert_eq!(result, Some(temp_dir.path().join("test-tool.toml")));
+    }
+
+    #[test]
+    fn test_find_tool_manifest_rejects_traversal_names() {
+        let temp_dir = tempdir().unwrap();
+        let manifest_dir = temp_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::write(temp_dir.path().join("secret.jsonc"), "{}").unwrap();
+        fs::write(manifest_dir.join("tool.jsonc"), "{}").unwrap();
+
+        let config = GlobalConfig {
+            manifest_sources: vec![local_source(&manifest_dir)],
+            ..Default::default()
+        };
+
+        let err = config.find_tool_manifest("../secret").unwrap_err();
+        assert!(err.to_string().contains("Invalid tool name '../secret'"));
+        let result = config
+            .find_tool_manifest("tool")
+            .unwrap()
+            .map(|found| found.path);
+        assert_eq!(result, Some(manifest_dir.join("tool.jsonc")));
+    }
+
+    /// Counts pulls and fails them, like an unreachable remote
+    struct OfflineGit(std::cell::Cell<usize>);
+
+    impl crate::remote::GitClient for OfflineGit {
+        fn clone_repo(&self, _url: &str, _branch: Option<&str>, _dest: &Path) -> Result<()> {
+            Err(Error::Network("offline".to_string()))
+        }
+
+        fn pull(&self, _dest: &Path) -> Result<()> {
+            self.0.set(self.0.get() + 1);
+            Err(Error::Network("offline".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_refresh_git_clone_keeps_the_clone_when_the_pull_fails() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("tool.jsonc"), "{}").unwrap();
+        let source = ManifestSource {
+            source_type: "git".to_string(),
+            path: "https://example.com/manifests.git".to_string(),
+            branch: None,
+            subdir: None,
+            auto_update: true,
+            auth: None,
+        };
+        let git = OfflineGit(Default::default());
+
+        GlobalConfig::refresh_git_clone(&source, &git, temp_dir.path());
+        assert_eq!(git.0.get(), 1);
+        assert!(temp_dir.path().join("tool.jsonc").is_file());
+    }
+
+    /// Records clone requests instead of running git
+    struct RecordingGit(std::cell::RefCell<Vec<(String, Option<String>, PathBuf)>>);
+
+    impl crate::remote::GitClient for RecordingGit {
+        fn clone_repo(&self, url: &str, branch: Option<&str>, dest: &Path) -> Result<()> {
+            self.0.borrow_mut().push((
+                url.to_string(),
+                branch.map(String::from),
+                dest.to_path_buf(),
+            ));
+            Ok(())
+        }
+
+        fn pull(&self, _dest: &Path) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_clone_git_source_clones_once() {
+        let temp_dir = tempdir().unwrap();
+        let dest = temp_dir.path().join("cache/repo");
+        let source = ManifestSource {
+            source_type: "git".to_string(),
+            path: "https://example.com/manifests.git".to_string(),
+            branch: Some("stable".to_string()),
+            subdir: None,
+            auto_update: true,
+            auth: None,
+        };
+        let git = RecordingGit(Default::default());
+
+        GlobalConfig::clone_git_source_into(&source, &git, &dest).unwrap();
+        assert_eq!(
+            git.0.borrow().as_slice(),
+            [(
+                "https://example.com/manifests.git".to_string(),
+                Some("stable".to_string()),
+                dest.clone()
+            )]
+        );
+        assert!(temp_dir.path().join("cache").is_dir());
+
+        // An existing clone is left alone
+        fs::create_dir_all(&dest).unwrap();
+        GlobalConfig::clone_git_source_into(&source, &git, &dest).unwrap();
+        assert_eq!(git.0.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_relative_tools_dir_resolves_against_config_dir() {
+        let config_path = Path::new("/home/user/.config/tool-meister/config.toml");
+
+        assert_eq!(
+            GlobalConfig::resolve_tools_dir(Path::new("tools"), config_path),
+            Path::new("/home/user/.config/tool-meister/tools")
+        );
+        assert_eq!(
+            GlobalConfig::resolve_tools_dir(Path::new("/opt/tools"), config_path),
+            Path::new("/opt/tools")
+        );
+    }
+
+    #[test]
+    fn test_ssh_git_sources_clone_over_ssh_url() {
+        for url in [
+            "git@github.com:example/repo.git",
+            "ssh://git@git.corp.example:2222/tools/manifests.git",
+            "deploy@git.corp.example:tools/manifests.git",
+        ] {
+            let mut config = GlobalConfig::default();
+            let path = config
+                .add_manifest_source("git".to_string(), url.to_string(), None, None, true, None)
+                .unwrap();
+            assert_eq!(path, url);
+
+            let temp_dir = tempdir().unwrap();
+            let dest = temp_dir.path().join("repo");
+            let git = RecordingGit(Default::default());
+            let source = config.manifest_sources.last().unwrap();
+            GlobalConfig::clone_git_source_into(source, &git, &dest).unwrap();
+            assert_eq!(git.0.borrow()[0].0, url);
+        }
+    }
+
+    #[test]
+    fn test_normalize_git_url() {
+        assert_eq!(
+            GlobalConfig::normalize_git_url("https://github.com/example/repo"),
+            "https://github.com/example/repo.git"
+        );
+        assert_eq!(
+            GlobalConfig::normalize_git_url("https://github.com/example/repo.git/"),
+            "https://github.com/example/repo.git"
+        );
+        assert_eq!(
+            GlobalConfig::normalize_git_url("https://git.example.com/repo/"),
+            "https://git.example.com/repo"
+        );
+        assert_eq!(
+            GlobalConfig::normalize_git_url("git@github.com:example/repo"),
+            "git@github.com:example/repo"
+        );
+    }
+
+    #[test]
+    fn test_equivalent_git_urls_share_cache_dir() {
+        let mut first = GlobalConfig::default();
+        let mut second = GlobalConfig::default();
+        let path = first
+            .add_manifest_source(
+                "git".to_string(),
+                "https://github.com/example/repo".to_string(),
+                None,
+                None,
+                true,
+                None,
+            )
+            .unwrap();
+        assert_eq!(path, "https://github.com/example/repo.git");
+        second
+            .add_manifest_source(
+                "git".to_string(),
+                "https://github.com/example/repo.git/".to_string(),
+                None,
+                None,
+                true,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            GlobalConfig::git_clone_dir(first.manifest_sources.last().unwrap()),
+            GlobalConfig::git_clone_dir(second.manifest_sources.last().unwrap())
+        );
+        assert!(
+            first
+                .add_manifest_source(
+                    "git".to_string(),
+                    "https://github.com/example/repo.git".to_string(),
+                    None,
+                    None,
+                    true,
+                    None,
+                )
+                .is_err()
+        );
     }
 }
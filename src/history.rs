@@ -0,0 +1,170 @@
+use crate::error::{Error, Result};
+use crate::global_config::GlobalConfig;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One install/update/build/run invocation, stored as a line of `history.jsonl`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    pub tool: String,
+    pub action: String,
+    pub status: String,
+    /// Start time in seconds since the Unix epoch
+    pub timestamp: u64,
+    pub duration_ms: u128,
+}
+
+/// History file kept next to the app configuration file
+pub fn history_path() -> PathBuf {
+    GlobalConfig::get_config_path().with_file_name("history.jsonl")
+}
+
+/// Seconds since the Unix epoch for `time`
+pub fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Append an entry to the history file
+pub fn append(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open history file: {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Read all entries, oldest first; a missing file is an empty history
+pub fn load(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read history file: {}", path.display()))?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Entries for `tool` (or every tool) that started at or after `cutoff`
+pub fn filter<'a>(
+    entries: &'a [HistoryEntry],
+    tool: Option<&str>,
+    cutoff: Option<u64>,
+) -> Vec<&'a HistoryEntry> {
+    entries
+        .iter()
+        .filter(|entry| tool.is_none_or(|tool| entry.tool == tool))
+        .filter(|entry| cutoff.is_none_or(|cutoff| entry.timestamp >= cutoff))
+        .collect()
+}
+
+/// Parse a human duration like `90s`, `30m`, `24h`, `7d` or `2w`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let invalid = || {
+        Error::Config(format!(
+            "Invalid duration '{}': expected a number followed by s, m, h, d or w",
+            text
+        ))
+    };
+
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    amount
+        .checked_mul(unit_seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+/// Short "how long ago" label for a timestamp
+pub fn format_age(timestamp: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(timestamp);
+    match seconds {
+        0..60 => format!("{}s ago", seconds),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(tool: &str, timestamp: u64) -> HistoryEntry {
+        HistoryEntry {
+            tool: tool.to_string(),
+            action: "run".to_string(),
+            status: "ok".to_string(),
+            timestamp,
+            duration_ms: 5,
+        }
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("24h").unwrap(), Duration::from_secs(86400));
+        assert_eq!(parse_duration("7d").unwrap(), Duration::from_secs(604800));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::from_secs(1209600));
+
+        for invalid in ["", "h", "24", "24x", "-1h", "1.5h"] {
+            assert!(parse_duration(invalid).is_err(), "accepted {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_filter_by_tool_and_cutoff() {
+        let entries = vec![entry("fd", 100), entry("rg", 200), entry("fd", 300)];
+
+        let recent = filter(&entries, None, Some(200));
+        assert_eq!(recent, vec![&entries[1], &entries[2]]);
+
+        let fd = filter(&entries, Some("fd"), None);
+        assert_eq!(fd, vec![&entries[0], &entries[2]]);
+
+        let recent_fd = filter(&entries, Some("fd"), Some(150));
+        assert_eq!(recent_fd, vec![&entries[2]]);
+    }
+
+    #[test]
+    fn test_append_and_load_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("nested/history.jsonl");
+
+        assert!(load(&path).unwrap().is_empty());
+        append(&path, &entry("fd", 1)).unwrap();
+        append(&path, &entry("rg", 2)).unwrap();
+
+        assert_eq!(load(&path).unwrap(), vec![entry("fd", 1), entry("rg", 2)]);
+    }
+}
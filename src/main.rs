@@ -1,32 +1,146 @@
-use clap::{Parser, Subcommand};
-use std::{env, path::PathBuf};
+use anyhow::Context;
+use clap::{CommandFactory, Parser, Subcommand};
+use std::{
+    env,
+    io::IsTerminal,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
 
 mod commands;
 mod config;
+mod credentials;
 mod error;
 mod global_config;
+mod history;
+mod output;
+mod picker;
+mod remote;
 
 use config::Config;
 use global_config::GlobalConfig;
+use output::{Marker, marker};
 
 #[derive(Parser)]
 #[command(name = env!("CARGO_PKG_NAME"))]
 #[command(about = format!("{} - manage and run tools in a workspace", env!("CARGO_PKG_NAME")))]
+#[command(version)]
 #[command(disable_help_subcommand = true)]
+#[command(after_help = "\
+Exit codes:
+  0    success
+  1    other error (I/O and unexpected failures)
+  2    configuration or manifest error
+  3    tool or manifest not found
+  4    an action's command failed
+  5    network error
+  130  cancelled or interrupted (SIGINT)
+  143  terminated (SIGTERM)")]
 struct Cli {
+    /// Fallback manifest directory (default: $TOOL_MEISTER_CONFIG_DIR, then default_manifest_dir)
     #[arg(short, long, global = true)]
     config_dir: Option<PathBuf>,
 
+    /// Override the tools directory for this invocation (relative to the current directory)
+    #[arg(long, global = true, value_name = "PATH")]
+    tools_dir: Option<PathBuf>,
+
+    /// Write each step's output to <PATH>/<tool>/<phase>/<seq_id>.log
+    #[arg(long, global = true, value_name = "PATH")]
+    output_dir: Option<PathBuf>,
+
+    /// Maximum number of tasks parallel operations run at once
+    #[arg(short = 'j', long, global = true, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: Option<u16>,
+
+    /// Suppress non-essential output (e.g. spawn banners)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print debug details to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Explain why each spawn-capable action spawns or waits
+    #[arg(long, global = true)]
+    explain: bool,
+
+    /// Print each action's resolved invocation (cwd, shell, argv, env names) before and after it runs
+    #[arg(long, global = true)]
+    trace_commands: bool,
+
+    /// Refresh every manifest source for this invocation, ignoring its auto_update setting
+    #[arg(long, global = true, conflicts_with = "no_update_sources")]
+    update_sources: bool,
+
+    /// Use cached manifests for this invocation, ignoring every source's auto_update setting
+    #[arg(long, global = true)]
+    no_update_sources: bool,
+
+    /// Override a manifest field for this invocation, e.g. --set repo.url=<fork> (repeatable)
+    #[arg(
+        long = "set",
+        alias = "manifest-override",
+        global = true,
+        value_name = "DOTPATH=VALUE"
+    )]
+    set: Vec<String>,
+
+    /// Attempt every action of a phase even after one fails, then report all failures
+    #[arg(long, global = true)]
+    no_fail_fast: bool,
+
+    /// Print a machine-readable result line after install/update/build/run
+    #[arg(long, global = true)]
+    summary: bool,
+
+    /// Use ASCII markers like [OK] instead of emojis (default when stdout isn't a terminal)
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// Refuse manifests that are world-writable instead of only warning
+    #[arg(long, global = true)]
+    strict_security: bool,
+
+    /// Never create or change the app configuration file (also set by TOOL_MEISTER_LOCKED)
+    #[arg(long, global = true)]
+    locked: bool,
+
+    /// Change to DIR before doing anything else, like `make -C`
+    #[arg(short = 'C', long, global = true, value_name = "DIR")]
+    chdir: Option<PathBuf>,
+
+    /// Print the app configuration file path and exit
+    #[arg(long)]
+    print_config_path: bool,
+
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Install a tool
+    /// Install one or more tools
     Install {
-        /// Tool name (corresponds to config file name without extension)
-        tool: String,
+        /// Tool names (correspond to config file names without extension)
+        #[arg(required = true, value_name = "TOOL")]
+        tools: Vec<String>,
+        /// Delete the tool's existing directory and install from scratch
+        #[arg(long)]
+        reinstall: bool,
+        /// Reinstall, moving the existing directory to <dir>.bak-<timestamp> instead of deleting it
+        #[arg(long)]
+        backup: bool,
+        /// Commit or tag to install, overriding the manifest's `ref` and default branch
+        #[arg(long = "ref", value_name = "REF")]
+        git_ref: Option<String>,
+        /// Also print a content hash of each install directory
+        #[arg(long)]
+        hash: bool,
+        /// Run each tool's verify actions after installing it
+        #[arg(long)]
+        verify: bool,
     },
     /// Update a tool
     Update {
@@ -37,21 +151,76 @@ enum Commands {
     Build {
         /// Tool name (corresponds to config file name without extension)
         tool: String,
+        /// Run the build actions in the current directory instead of the tool's directory
+        /// (git_clone and git_pull actions still work on the tool's repository)
+        #[arg(long)]
+        no_cd: bool,
+    },
+    /// Check that an installed tool works by running its verify actions
+    Verify {
+        /// Tool name (corresponds to config file name without extension)
+        tool: String,
     },
     /// Run a tool
     #[command(trailing_var_arg = true)]
     Run {
-        /// Tool name (corresponds to config file name without extension)
-        tool: String,
         /// Force spawn mode (detach process) even with arguments
         #[arg(long, short = 's')]
         spawn: bool,
         /// Wait for completion even when spawn=true in config
         #[arg(long, short = 'w')]
         wait: bool,
-        /// Additional arguments to pass to the tool
-        #[arg(allow_hyphen_values = true)]
-        args: Vec<String>,
+        /// Spawn without printing the "Spawned ... (PID: N)" banner
+        #[arg(long)]
+        quiet_spawn: bool,
+        /// Verify (and install if needed) declared dependencies before running
+        #[arg(long)]
+        with_deps: bool,
+        /// Run commands exactly as written in the manifest, without [[...]] substitution
+        #[arg(long)]
+        no_interpolate: bool,
+        /// Only forward arguments matching this regular expression
+        #[arg(long, value_name = "REGEX")]
+        filter: Option<String>,
+        /// Re-run whenever files under this path change (Ctrl-C to stop)
+        #[arg(long, value_name = "PATH")]
+        watch: Option<PathBuf>,
+        /// Read additional arguments from a file (placed before inline arguments)
+        #[arg(long, value_name = "PATH")]
+        args_file: Option<PathBuf>,
+        /// Spawn the run actions in the background, logging their output and recording PIDs
+        #[arg(long, conflicts_with = "wait")]
+        detach: bool,
+        /// Run the actions in the current directory instead of the tool's directory
+        /// (git_clone and git_pull actions still work on the tool's repository)
+        #[arg(long)]
+        no_cd: bool,
+        /// Set an environment variable for the run actions (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env_vars: Vec<String>,
+        /// Print the environment the run actions would receive instead of running them
+        #[arg(long)]
+        print_env: bool,
+        /// Tool name (corresponds to config file name without extension), then arguments to
+        /// pass to it; everything after the tool name is passed on, so options go before it.
+        /// When the tool is omitted on a terminal, pick one interactively
+        #[arg(value_name = "TOOL [ARGS]", allow_hyphen_values = true)]
+        tool_and_args: Vec<String>,
+    },
+    /// Install, update, build and run a tool in sequence
+    #[command(trailing_var_arg = true)]
+    All {
+        /// Continue with the remaining phases after a failure
+        #[arg(long)]
+        keep_going: bool,
+        /// Tool name (corresponds to config file name without extension), then arguments for
+        /// its run phase; everything after the tool name is passed on, so options go before it
+        #[arg(
+            value_name = "TOOL [ARGS]",
+            required = true,
+            allow_hyphen_values = true
+        )]
+        tool_and_args: Vec<String>,
     },
     /// Generate global configuration file
     Config {
@@ -61,10 +230,58 @@ enum Commands {
         /// Reset to default configuration
         #[arg(long)]
         reset: bool,
+        /// With --reset, start without any manifest sources
+        #[arg(long, requires = "reset")]
+        empty: bool,
+        /// With --reset, keep the currently configured manifest sources
+        #[arg(long, requires = "reset", conflicts_with = "empty")]
+        keep_sources: bool,
+        /// Output format for --show
+        #[arg(long, value_enum, default_value_t)]
+        format: commands::config::ConfigFormat,
     },
     /// Manage manifest sources where the app looks for new tool manifests
     #[command(subcommand)]
     Manifests(ManifestCommands),
+    /// Print version and build metadata
+    Version,
+    /// Generate or install shell completion scripts
+    #[command(subcommand, alias = "completions")]
+    Completion(CompletionCommands),
+    /// Preview the steps a phase would run, without running anything
+    Plan {
+        /// Tool name (corresponds to config file name without extension)
+        tool: String,
+        /// Phase to plan
+        #[arg(value_parser = clap::builder::PossibleValuesParser::new(config::Actions::PHASES))]
+        phase: String,
+    },
+    /// Show recorded install/update/build/run results
+    History {
+        /// Only show entries for this tool
+        tool: Option<String>,
+        /// Only show entries newer than this (e.g. 30m, 24h, 7d)
+        #[arg(long, value_name = "DURATION")]
+        since: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CompletionCommands {
+    /// Print the completion script for a shell
+    Generate {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Write the completion script where the shell looks for it
+    Install {
+        /// Shell to install for (default: detected from $SHELL)
+        #[arg(long, value_enum)]
+        shell: Option<clap_complete::Shell>,
+        /// Overwrite an existing completion script
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -79,9 +296,21 @@ enum ManifestCommands {
         /// Branch for git sources (optional)
         #[arg(short, long)]
         branch: Option<String>,
+        /// Subdirectory within the source that holds the manifests (optional)
+        #[arg(long)]
+        subdir: Option<String>,
         /// Disable automatic updates
         #[arg(long)]
         no_auto_update: bool,
+        /// Send a token when fetching this source, read from the environment or OS keyring
+        #[arg(long, value_enum)]
+        auth: Option<global_config::AuthMethod>,
+        /// Environment variable holding the token (default: TOOL_MEISTER_TOKEN)
+        #[arg(long, value_name = "VAR", requires = "auth")]
+        token_env: Option<String>,
+        /// Clone a git source into the cache right away so it's usable offline
+        #[arg(long)]
+        clone_now: bool,
     },
     /// List all configured manifest sources
     List,
@@ -90,65 +319,664 @@ enum ManifestCommands {
         /// Show detailed information for specific source
         #[arg(short, long)]
         source: Option<String>,
+        /// Also list tools cached locally for git and url sources
+        #[arg(long, alias = "include-cache")]
+        all_sources: bool,
+        /// Check each source is reachable and each of its manifests parses
+        #[arg(long, conflicts_with_all = ["all_sources", "limit", "sort"])]
+        check: bool,
+        /// List at most N tools per source
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+        /// Order of the listed tools
+        #[arg(long, value_enum, default_value_t = ManifestSort::Source)]
+        sort: ManifestSort,
+    },
+    /// Change the branch of a git manifest source
+    SetBranch {
+        /// Source index (as shown by `list`) or path
+        source: String,
+        /// New branch to track
+        branch: String,
+    },
+    /// Change the path or URL of a manifest source
+    SetPath {
+        /// Source index (as shown by `list`) or path
+        source: String,
+        /// New path or URL for the source
+        path: String,
+    },
+    /// Show how a source's manifests changed since the last diff
+    Diff {
+        /// Source index (as shown by `list`) or path
+        source: String,
+    },
+    /// Check that git and url sources are reachable without cloning or downloading them
+    Check {
+        /// Source index (as shown by `list`) or path; checks every source when omitted
+        source: Option<String>,
+    },
+    /// Fetch the latest manifests of every git and url source (parallel, bounded by --jobs)
+    Update {
+        /// Source index (as shown by `list`) or path; updates every remote source when omitted
+        source: Option<String>,
+    },
+    /// Print one tool's manifest: repository, dependencies and actions
+    Show {
+        /// Tool name to resolve through the manifest sources
+        tool: String,
+        /// Print the parsed manifest as JSON
+        #[arg(long)]
+        json: bool,
+        /// Write the manifest as JSON with `--set` overrides applied and action commands
+        /// interpolated, to PATH or stdout
+        #[arg(
+            long,
+            value_name = "PATH",
+            num_args = 0..=1,
+            default_missing_value = "-",
+            conflicts_with = "json"
+        )]
+        dump_resolved: Option<PathBuf>,
+    },
+    /// Write a new manifest for a tool, from a starter template or a blank one
+    New {
+        /// Name of the new tool
+        name: String,
+        /// Repository URL, substituted for {{url}} in the starter
+        #[arg(long)]
+        url: Option<String>,
+        /// Starter manifest to copy: a local path or a file://, http:// or https:// URL
+        #[arg(long, value_name = "PATH_OR_URL")]
+        from: Option<String>,
+        /// Where to write the manifest (default: <name>.<ext> in the manifest directory)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// Overwrite an existing manifest
+        #[arg(long)]
+        force: bool,
     },
 }
 
+/// Order `manifests info` lists a source's tools in
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+enum ManifestSort {
+    /// As the source provides them
+    #[default]
+    Source,
+    /// Alphabetically
+    Name,
+}
+
+/// How `manifests info` lists each source's tools
+#[derive(Debug, Clone, Copy, Default)]
+struct ListingOptions {
+    limit: Option<usize>,
+    sort: ManifestSort,
+}
+
+/// Environment variable supplying the manifest directory when `--config-dir` isn't given
+const CONFIG_DIR_ENV: &str = "TOOL_MEISTER_CONFIG_DIR";
+
+/// Environment variable turning on `--locked`
+const LOCKED_ENV: &str = "TOOL_MEISTER_LOCKED";
+
 #[tokio::main]
-async fn main() -> crate::error::Result<()> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(commands::signal_exit_code().unwrap_or_else(|| e.exit_code()))
+        }
+    }
+}
+
+async fn run() -> crate::error::Result<()> {
     let cli = Cli::parse();
-    let global_config = GlobalConfig::load()?;
+    output::set_plain(cli.plain || !std::io::stdout().is_terminal());
+    let cancel = tokio_util::sync::CancellationToken::new();
+    commands::handle_signals(cancel.clone()).context("Failed to install signal handlers")?;
+
+    if let Some(dir) = &cli.chdir {
+        if !dir.is_dir() {
+            return Err(crate::error::Error::Config(format!(
+                "--chdir: not a directory: {}",
+                dir.display()
+            )));
+        }
+        env::set_current_dir(dir)
+            .with_context(|| format!("Failed to change directory to {}", dir.display()))?;
+    }
 
-    // Determine manifest directory: CLI arg > global config > default
+    if cli.print_config_path {
+        println!("{}", GlobalConfig::get_config_path().display());
+        return Ok(());
+    }
+
+    let Some(command) = cli.command else {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "a subcommand is required",
+            )
+            .exit();
+    };
+
+    global_config::set_locked(
+        cli.locked || global_config::locked_env_value(env::var(LOCKED_ENV).ok().as_deref()),
+    );
+    let mut global_config = GlobalConfig::load()?;
+    if let Some(tools_dir) = cli.tools_dir {
+        // Unlike the configured value, a path given on the command line is relative to the cwd
+        global_config.tools_dir = std::path::absolute(&tools_dir).unwrap_or(tools_dir);
+    }
+    if let Some(jobs) = cli.jobs {
+        global_config.max_concurrency = jobs.into();
+    }
+    if let Some(output_dir) = cli.output_dir {
+        global_config.output_dir = Some(output_dir);
+    }
+    if cli.update_sources || cli.no_update_sources {
+        global_config.update_sources = Some(cli.update_sources);
+    }
+
+    // Determine manifest directory: CLI arg > environment > global config
     let config_dir = cli
         .config_dir
+        .or_else(|| {
+            env::var_os(CONFIG_DIR_ENV)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+        })
         .unwrap_or_else(|| global_config.default_manifest_dir.clone());
 
-    match cli.command {
-        Commands::Install { tool } => {
-            let config = load_tool_config(&global_config, &config_dir, &tool)?;
-            commands::install::install_command(&config, &global_config).await?;
-            println!(
-                "✅ Installation of {} completed successfully!",
-                config.repo.name
+    let exec_options = commands::ExecOptions {
+        quiet_spawn: cli.quiet,
+        verbose: cli.verbose,
+        explain: cli.explain,
+        trace_commands: cli.trace_commands,
+        no_fail_fast: cli.no_fail_fast,
+        spawn_mode: global_config.spawn_mode,
+        workspace_dir: Some(global_config.workspace_dir()?),
+        cancel,
+        ..Default::default()
+    };
+
+    match command {
+        Commands::Install {
+            tools,
+            reinstall,
+            backup,
+            git_ref,
+            hash,
+            verify,
+        } => {
+            let exec_options = commands::ExecOptions {
+                content_hash: hash,
+                verify,
+                ..exec_options
+            };
+            let existing = if backup {
+                commands::install::ExistingInstall::Backup
+            } else if reinstall {
+                commands::install::ExistingInstall::Remove
+            } else {
+                commands::install::ExistingInstall::Reuse
+            };
+            if tools.len() > 1 {
+                if git_ref.is_some() {
+                    return Err(crate::error::Error::Config(
+                        "--ref can only be used when installing a single tool".to_string(),
+                    ));
+                }
+                let configs = tools
+                    .iter()
+                    .map(|tool| {
+                        load_tool_config(
+                            &global_config,
+                            &config_dir,
+                            tool,
+                            cli.strict_security,
+                            &cli.set,
+                            cli.verbose,
+                        )
+                    })
+                    .collect::<crate::error::Result<Vec<_>>>()?;
+                let progress = !cli.plain
+                    && std::io::stdout().is_terminal()
+                    && std::io::stderr().is_terminal();
+                let outcomes = commands::install::install_many_command(
+                    &configs,
+                    &global_config,
+                    existing,
+                    &exec_options,
+                    progress,
+                )
+                .await;
+
+                let mut failed = 0;
+                for ((tool, config), (elapsed, result)) in tools.iter().zip(&configs).zip(outcomes)
+                {
+                    let steps = config.actions.installation.len();
+                    report_result(cli.summary, tool, "install", steps, elapsed, &result);
+                    if let Err(e) = result {
+                        failed += 1;
+                        eprintln!(
+                            "{} Installation of {} failed: {}",
+                            marker(Marker::Fail),
+                            config.repo.name,
+                            e
+                        );
+                    }
+                }
+                if failed > 0 {
+                    return Err(crate::error::Error::Command(format!(
+                        "{} of {} tools failed to install",
+                        failed,
+                        tools.len()
+                    )));
+                }
+                return Ok(());
+            }
+
+            let tool = &tools[0];
+            let mut config = load_tool_config(
+                &global_config,
+                &config_dir,
+                tool,
+                cli.strict_security,
+                &cli.set,
+                cli.verbose,
+            )?;
+            if git_ref.is_some() {
+                config.repo.git_ref = git_ref;
+            }
+            let started = Instant::now();
+            let result = commands::install::install_command(
+                &config,
+                &global_config,
+                existing,
+                &exec_options,
+            )
+            .await;
+            if result.is_ok() {
+                println!(
+                    "{} Installation of {} completed successfully!",
+                    marker(Marker::Ok),
+                    config.repo.name
+                );
+            }
+            let steps = config.actions.installation.len();
+            report_result(
+                cli.summary,
+                tool,
+                "install",
+                steps,
+                started.elapsed(),
+                &result,
             );
+            result?;
         }
         Commands::Update { tool } => {
-            let config = load_tool_config(&global_config, &config_dir, &tool)?;
-            commands::update::update_command(&config, &global_config).await?;
-            println!("✅ Update of {} completed successfully!", config.repo.name);
+            let config = load_tool_config(
+                &global_config,
+                &config_dir,
+                &tool,
+                cli.strict_security,
+                &cli.set,
+                cli.verbose,
+            )?;
+            let started = Instant::now();
+            let result =
+                commands::update::update_command(&config, &global_config, &exec_options).await;
+            if result.is_ok() {
+                println!(
+                    "{} Update of {} completed successfully!",
+                    marker(Marker::Ok),
+                    config.repo.name
+                );
+            }
+            let steps = config.actions.update.len();
+            report_result(
+                cli.summary,
+                &tool,
+                "update",
+                steps,
+                started.elapsed(),
+                &result,
+            );
+            result?;
         }
-        Commands::Build { tool } => {
-            let config = load_tool_config(&global_config, &config_dir, &tool)?;
-            commands::build::build_command(&config, &global_config).await?;
-            println!("✅ Build of {} completed successfully!", config.repo.name);
+        Commands::Verify { tool } => {
+            let config = load_tool_config(
+                &global_config,
+                &config_dir,
+                &tool,
+                cli.strict_security,
+                &cli.set,
+                cli.verbose,
+            )?;
+            let started = Instant::now();
+            let result =
+                commands::verify::verify_command(&config, &global_config, &exec_options).await;
+            if result.is_ok() {
+                println!(
+                    "{} Verification of {} passed!",
+                    marker(Marker::Ok),
+                    config.repo.name
+                );
+            }
+            let steps = config.actions.verify.len();
+            report_result(
+                cli.summary,
+                &tool,
+                "verify",
+                steps,
+                started.elapsed(),
+                &result,
+            );
+            result?;
+        }
+        Commands::Build { tool, no_cd } => {
+            let config = load_tool_config(
+                &global_config,
+                &config_dir,
+                &tool,
+                cli.strict_security,
+                &cli.set,
+                cli.verbose,
+            )?;
+            let started = Instant::now();
+            let options = commands::ExecOptions {
+                no_cd,
+                ..exec_options
+            };
+            let result = commands::build::build_command(&config, &global_config, &options).await;
+            if result.is_ok() {
+                println!(
+                    "{} Build of {} completed successfully!",
+                    marker(Marker::Ok),
+                    config.repo.name
+                );
+            }
+            let steps = config.actions.build.len();
+            report_result(
+                cli.summary,
+                &tool,
+                "build",
+                steps,
+                started.elapsed(),
+                &result,
+            );
+            result?;
         }
         Commands::Run {
-            tool,
             spawn,
             wait,
-            args,
+            quiet_spawn,
+            with_deps,
+            no_interpolate,
+            filter,
+            watch,
+            args_file,
+            detach,
+            no_cd,
+            env_vars,
+            print_env,
+            tool_and_args,
         } => {
-            let config = load_tool_config(&global_config, &config_dir, &tool)?;
-            commands::run::run_command(&config, &args, spawn, wait, &global_config).await?;
-            println!("✅ {} execution completed!", config.repo.name);
+            let (tool, args) = split_tool_args(tool_and_args);
+            let tool = match tool {
+                Some(tool) => tool,
+                None => pick_tool(&global_config, &config_dir)?,
+            };
+            let config = load_tool_config(
+                &global_config,
+                &config_dir,
+                &tool,
+                cli.strict_security,
+                &cli.set,
+                cli.verbose,
+            )?;
+            let args = match args_file {
+                Some(path) => {
+                    let mut file_args = commands::run::read_args_file(&path)?;
+                    file_args.extend(args);
+                    file_args
+                }
+                None => args,
+            };
+            let args = match filter {
+                Some(pattern) => commands::run::filter_args(args, &pattern)?,
+                None => args,
+            };
+            let options = commands::ExecOptions {
+                force_spawn: spawn,
+                force_wait: wait,
+                quiet_spawn: quiet_spawn || cli.quiet,
+                with_deps,
+                no_interpolate,
+                env: commands::run::parse_env_vars(&env_vars)?,
+                detach,
+                no_cd,
+                ..exec_options
+            };
+            if print_env {
+                return commands::run::print_env_command(&config, &options, &global_config);
+            }
+            if let Some(watch_path) = watch {
+                return commands::run::watch_command(
+                    &config,
+                    &args,
+                    &options,
+                    &global_config,
+                    &watch_path,
+                )
+                .await;
+            }
+
+            let started = Instant::now();
+            let result = commands::run::run_command(&config, &args, &options, &global_config).await;
+            if result.is_ok() {
+                println!(
+                    "{} {} execution completed!",
+                    marker(Marker::Ok),
+                    config.repo.name
+                );
+            }
+            let steps = config.actions.run.len();
+            report_result(cli.summary, &tool, "run", steps, started.elapsed(), &result);
+            result?;
         }
-        Commands::Config { show, reset } => {
-            commands::config::config_command(show, reset, &global_config).await?;
+        Commands::All {
+            keep_going,
+            tool_and_args,
+        } => {
+            let (tool, args) = split_tool_args(tool_and_args);
+            let tool = tool.expect("clap requires the tool name");
+            let config = load_tool_config(
+                &global_config,
+                &config_dir,
+                &tool,
+                cli.strict_security,
+                &cli.set,
+                cli.verbose,
+            )?;
+            let started = Instant::now();
+            let result = commands::all::all_command(
+                &config,
+                &args,
+                keep_going,
+                &exec_options,
+                &global_config,
+            )
+            .await;
+            if result.is_ok() {
+                println!(
+                    "{} All phases of {} completed successfully!",
+                    marker(Marker::Ok),
+                    config.repo.name
+                );
+            }
+            let steps = config
+                .actions
+                .iter_phases()
+                .filter(|(phase, _)| commands::all::runs_phase(phase))
+                .map(|(_, actions)| actions.len())
+                .sum();
+            report_result(cli.summary, &tool, "all", steps, started.elapsed(), &result);
+            result?;
+        }
+        Commands::Config {
+            show,
+            reset,
+            empty,
+            keep_sources,
+            format,
+        } => {
+            commands::config::config_command(
+                show,
+                reset,
+                empty,
+                keep_sources,
+                format,
+                &global_config,
+            )
+            .await?;
+        }
+        Commands::Version => {
+            commands::version::version_command(&global_config).await?;
+        }
+        Commands::Completion(CompletionCommands::Generate { shell }) => {
+            commands::completion::generate_command(shell, Cli::command())?;
+        }
+        Commands::Completion(CompletionCommands::Install { shell, force }) => {
+            commands::completion::install_command(shell, force, Cli::command())?;
+        }
+        Commands::Plan { tool, phase } => {
+            let config = load_tool_config(
+                &global_config,
+                &config_dir,
+                &tool,
+                cli.strict_security,
+                &cli.set,
+                cli.verbose,
+            )?;
+            commands::plan::plan_command(&config, &phase, &global_config)?;
+        }
+        Commands::History { tool, since } => {
+            commands::history::history_command(tool.as_deref(), since.as_deref()).await?;
         }
         Commands::Manifests(manifest_cmd) => match manifest_cmd {
             ManifestCommands::AddSource {
                 source_type,
                 path,
                 branch,
+                subdir,
                 no_auto_update,
+                auth,
+                token_env,
+                clone_now,
             } => {
-                add_manifest_source(source_type, path, branch, !no_auto_update)?;
+                let auth = auth.map(|method| global_config::SourceAuth { method, token_env });
+                let system_git = global_config.git_client();
+                let git = clone_now.then_some(&system_git as &dyn remote::GitClient);
+                add_manifest_source(
+                    source_type,
+                    path,
+                    branch,
+                    subdir,
+                    !no_auto_update,
+                    auth,
+                    git,
+                )?;
             }
             ManifestCommands::List => {
                 list_manifest_sources(&global_config)?;
             }
-            ManifestCommands::Info { source } => {
-                show_manifest_info(&global_config, &source).await?;
+            ManifestCommands::Info {
+                source,
+                check: true,
+                ..
+            } => {
+                check_manifest_health(&global_config, source.as_deref())?;
+            }
+            ManifestCommands::Info {
+                source,
+                all_sources,
+                limit,
+                sort,
+                ..
+            } => {
+                let listing = ListingOptions { limit, sort };
+                show_manifest_info(&global_config, &source, all_sources, listing).await?;
+            }
+            ManifestCommands::SetBranch { source, branch } => {
+                let mut config = GlobalConfig::load()?;
+                config.set_source_branch(&source, branch.clone())?;
+                config.save()?;
+                println!(
+                    "{} Set branch of manifest source {} to {}",
+                    marker(Marker::Ok),
+                    source,
+                    branch
+                );
+            }
+            ManifestCommands::SetPath { source, path } => {
+                let mut config = GlobalConfig::load()?;
+                let validated_path = config.set_source_path(&source, path)?;
+                config.save()?;
+                println!(
+                    "{} Set path of manifest source {} to {}",
+                    marker(Marker::Ok),
+                    source,
+                    validated_path
+                );
+            }
+            ManifestCommands::Diff { source } => {
+                diff_manifest_source(&global_config, &source)?;
+            }
+            ManifestCommands::Check { source } => {
+                check_manifest_sources(&global_config, source.as_deref())?;
+            }
+            ManifestCommands::Update { source } => {
+                update_manifest_sources(&global_config, source.as_deref()).await?;
+            }
+            ManifestCommands::Show {
+                tool,
+                dump_resolved: Some(path),
+                ..
+            } => {
+                let config = load_tool_config(
+                    &global_config,
+                    &config_dir,
+                    &tool,
+                    cli.strict_security,
+                    &cli.set,
+                    cli.verbose,
+                )?;
+                dump_resolved_manifest(&global_config, &config, &path)?;
+            }
+            ManifestCommands::Show { tool, json, .. } => {
+                show_tool_manifest(&global_config, &tool, json)?;
+            }
+            ManifestCommands::New {
+                name,
+                url,
+                from,
+                output,
+                force,
+            } => {
+                new_manifest(
+                    &global_config,
+                    &config_dir,
+                    &name,
+                    url.as_deref(),
+                    from.as_deref(),
+                    output,
+                    force,
+                )?;
             }
         },
     }
@@ -156,32 +984,125 @@ async fn main() -> crate::error::Result<()> {
     Ok(())
 }
 
+/// Record the result in the run history and print the machine-readable
+/// `TOOL_MEISTER_RESULT` line when `--summary` is set
+fn report_result<T>(
+    summary: bool,
+    tool: &str,
+    action: &str,
+    steps: usize,
+    elapsed: Duration,
+    result: &crate::error::Result<T>,
+) {
+    let status = if result.is_ok() { "ok" } else { "error" };
+
+    let entry = history::HistoryEntry {
+        tool: tool.to_string(),
+        action: action.to_string(),
+        status: status.to_string(),
+        timestamp: history::unix_seconds(std::time::SystemTime::now() - elapsed),
+        duration_ms: elapsed.as_millis(),
+    };
+    if let Err(e) = history::append(&history::history_path(), &entry) {
+        eprintln!(
+            "{} Failed to record run history: {}",
+            marker(Marker::Warn),
+            e
+        );
+    }
+
+    if !summary {
+        return;
+    }
+
+    println!(
+        "TOOL_MEISTER_RESULT tool={} action={} status={} steps={} duration_ms={}",
+        tool,
+        action,
+        status,
+        steps,
+        elapsed.as_millis()
+    );
+}
+
 fn load_tool_config(
     global_config: &GlobalConfig,
     fallback_dir: &std::path::Path,
     tool_name: &str,
+    strict_security: bool,
+    overrides: &[String],
+    verbose: bool,
 ) -> crate::error::Result<Config> {
-    // First try to find manifest through global config sources
-    if let Some(manifest_path) = global_config.find_tool_manifest(tool_name)? {
-        return Config::load_from_path(&manifest_path);
-    }
+    // First try to find manifest through global config sources, then fall back to local directory
+    let (mut config, manifest_path) = match global_config.find_tool_manifest(tool_name)? {
+        Some(found) => {
+            if verbose {
+                eprintln!(
+                    "[debug] Using manifest for {} from {}: {}",
+                    tool_name,
+                    found.provenance(),
+                    found.path.display()
+                );
+            }
+            config::check_manifest_permissions(&found.path, strict_security)?;
+            (Config::load_from_path(&found.path)?, found.path)
+        }
+        None => {
+            let formats = &global_config.manifest_formats;
+            config::validate_tool_name(tool_name)?;
+            let fallback_path = Config::manifest_path(fallback_dir, tool_name, formats);
+            if !fallback_path.is_file() {
+                return Err(crate::error::Error::NotFound(format!(
+                    "No manifest found for '{}' in any manifest source or {}",
+                    tool_name,
+                    fallback_dir.display()
+                )));
+            }
+            if verbose {
+                eprintln!(
+                    "[debug] Using manifest for {} from the fallback directory: {}",
+                    tool_name,
+                    fallback_path.display()
+                );
+            }
+            config::check_manifest_permissions(&fallback_path, strict_security)?;
+            (
+                Config::load(fallback_dir, tool_name, formats)?,
+                fallback_path,
+            )
+        }
+    };
+    config.manifest_dir = manifest_path
+        .parent()
+        .map(|dir| std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf()));
 
-    // Fall back to local directory
-    Config::load(fallback_dir, tool_name)
+    for assignment in overrides {
+        config.apply_override(assignment)?;
+    }
+    Ok(config)
 }
 
 fn add_manifest_source(
     source_type: String,
     path: String,
     branch: Option<String>,
+    subdir: Option<String>,
     auto_update: bool,
+    auth: Option<global_config::SourceAuth>,
+    clone_with: Option<&dyn remote::GitClient>,
 ) -> crate::error::Result<()> {
     // Load current config (prefer project-local if available)
     let mut config = GlobalConfig::load()?;
 
     // Add the new source and get the validated path
-    let validated_path =
-        config.add_manifest_source(source_type.clone(), path, branch.clone(), auto_update)?;
+    let validated_path = config.add_manifest_source(
+        source_type.clone(),
+        path,
+        branch.clone(),
+        subdir.clone(),
+        auto_update,
+        auth,
+    )?;
 
     // Save the updated config
     config.save()?;
@@ -192,6 +1113,11 @@ fn add_manifest_source(
         None => String::new(),
     };
 
+    let subdir_info = match subdir {
+        Some(ref d) => format!(" (subdir: {})", d),
+        None => String::new(),
+    };
+
     let auto_update_info = if auto_update {
         " with auto-update"
     } else {
@@ -199,13 +1125,46 @@ fn add_manifest_source(
     };
 
     println!(
-        "✅ Added manifest source: {} {}{}{}",
-        source_type, validated_path, branch_info, auto_update_info
+        "{} Added manifest source: {} {}{}{}{}",
+        marker(Marker::Ok),
+        source_type,
+        validated_path,
+        branch_info,
+        subdir_info,
+        auto_update_info
     );
 
+    if let Some(git) = clone_with {
+        match config.manifest_sources.last() {
+            Some(source) if source.source_type == "git" => clone_source_now(source, git),
+            _ => eprintln!(
+                "{} --clone-now only applies to git sources",
+                marker(Marker::Warn)
+            ),
+        }
+    }
+
     Ok(())
 }
 
+/// Clone a just-added git source; failure only warns since the source is already saved
+fn clone_source_now(source: &global_config::ManifestSource, git: &dyn remote::GitClient) {
+    match GlobalConfig::clone_git_source(source, git) {
+        Ok(dir) => println!(
+            "{} Cloned {} into {}",
+            marker(Marker::Ok),
+            source.path,
+            dir.display()
+        ),
+        Err(e) => eprintln!(
+            "{} Initial clone of {} failed: {}",
+            marker(Marker::Warn),
+            source.path,
+            e
+        ),
+    }
+}
+
 fn list_manifest_sources(global_config: &GlobalConfig) -> crate::error::Result<()> {
     println!("Configured manifest sources:");
 
@@ -224,15 +1183,27 @@ fn list_manifest_sources(global_config: &GlobalConfig) -> crate::error::Result<(
             Some(branch) => format!(" (branch: {})", branch),
             None => String::new(),
         };
+        let subdir_info = match &source.subdir {
+            Some(subdir) => format!(" (subdir: {})", subdir),
+            None => String::new(),
+        };
 
         println!(
-            "  {}: {} {} [{}]{}",
+            "  {}: {} {} [{}]{}{}",
             index + 1,
             source.source_type,
             source.path,
             auto_update_status,
-            branch_info
+            branch_info,
+            subdir_info
         );
+        if !source.has_known_type() {
+            println!(
+                "     {} unknown source type '{}'; this source is ignored",
+                marker(Marker::Warn),
+                source.source_type
+            );
+        }
     }
 
     Ok(())
@@ -241,19 +1212,56 @@ fn list_manifest_sources(global_config: &GlobalConfig) -> crate::error::Result<(
 async fn show_manifest_info(
     global_config: &GlobalConfig,
     source_filter: &Option<String>,
+    include_cache: bool,
+    listing_options: ListingOptions,
 ) -> crate::error::Result<()> {
     println!("Manifest source information:");
 
-    for (index, source) in global_config.manifest_sources.iter().enumerate() {
+    let sources: Vec<_> = global_config
+        .unique_sources()
+        .into_iter()
         // If source filter is provided, skip sources that don't match
-        if let Some(filter) = source_filter {
-            if !source.path.contains(filter) && !source.source_type.contains(filter) {
-                continue;
+        .filter(|(_, source)| match source_filter {
+            Some(filter) => source.path.contains(filter) || source.source_type.contains(filter),
+            None => true,
+        })
+        .collect();
+
+    // Fetch the url sources' indexes concurrently, then report every source in order
+    let mut indexes = commands::run_bounded(
+        sources
+            .iter()
+            .filter(|(_, source)| source.source_type == "url")
+            .map(|(_, source)| (*source).clone())
+            .collect(),
+        global_config.max_concurrency,
+        |source| {
+            let refresh = global_config.refreshes(&source);
+            let policy = global_config.network_policy();
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    GlobalConfig::fetch_source_index(&source, refresh, &policy)
+                })
+                .await
+                .context("Failed to fetch manifest index")?
             }
-        }
+        },
+    )
+    .await
+    .into_iter();
 
+    for (index, source) in sources {
+        let listing = match listing_dir(source, include_cache) {
+            Some(dir) if dir.exists() => Some(
+                manifest_files(&dir, &global_config.manifest_formats)?
+                    .into_keys()
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
         println!(
-            "\n📁 Source {}: {} {}",
+            "\n{} Source {}: {} {}",
+            marker(Marker::Source),
             index + 1,
             source.source_type,
             source.path
@@ -261,28 +1269,16 @@ async fn show_manifest_info(
 
         match source.source_type.as_str() {
             "local" => {
-                let manifest_dir = std::path::PathBuf::from(&source.path);
-                if manifest_dir.exists() {
-                    let entries = std::fs::read_dir(&manifest_dir)?;
-                    let mut manifest_count = 0;
-
+                if let Some(names) = listing {
                     println!("  Available manifests:");
-                    for entry in entries {
-                        let entry = entry?;
-                        let path = entry.path();
-                        if path.is_file() && path.extension().is_some_and(|ext| ext == "jsonc") {
-                            if let Some(name) = path.file_stem() {
-                                println!("    - {}", name.to_string_lossy());
-                                manifest_count += 1;
-                            }
-                        }
-                    }
-
-                    if manifest_count == 0 {
-                        println!("    No manifest files found");
-                    }
+                    print_manifest_names(&names, listing_options);
                 } else {
-                    println!("  ⚠️  Directory not found: {}", source.path);
+                    let manifest_dir = source.join_subdir(std::path::PathBuf::from(&source.path));
+                    println!(
+                        "  {} Directory not found: {}",
+                        marker(Marker::Warn),
+                        manifest_dir.display()
+                    );
                 }
             }
             "git" => {
@@ -299,6 +1295,9 @@ async fn show_manifest_info(
                     }
                 );
                 println!("  Note: Use 'update' command to fetch latest manifests");
+                if include_cache {
+                    print_cached_manifests(source, listing, listing_options);
+                }
             }
             "url" => {
                 println!("  URL source");
@@ -310,10 +1309,25 @@ async fn show_manifest_info(
                         "disabled"
                     }
                 );
+                match indexes.next().expect("one index per url source") {
+                    Ok(Some(names)) => {
+                        println!("  Available manifests (from index.json):");
+                        print_manifest_names(&names, listing_options);
+                    }
+                    Ok(None) => println!("  No index.json published; tools are fetched on demand"),
+                    Err(e) => println!("  {} Could not fetch index: {}", marker(Marker::Warn), e),
+                }
                 println!("  Note: Remote manifest content will be cached locally");
+                if include_cache {
+                    print_cached_manifests(source, listing, listing_options);
+                }
             }
             _ => {
-                println!("  ⚠️  Unknown source type: {}", source.source_type);
+                println!(
+                    "  {} Unknown source type: {}",
+                    marker(Marker::Warn),
+                    source.source_type
+                );
             }
         }
     }
@@ -333,6 +1347,541 @@ async fn show_manifest_info(
     Ok(())
 }
 
+/// Check every source matching `source_filter`: whether it can be reached, and whether
+/// each manifest it holds (locally, or cached for remote sources) parses
+fn check_manifest_health(
+    global_config: &GlobalConfig,
+    source_filter: Option<&str>,
+) -> crate::error::Result<()> {
+    let mut problems = 0;
+    let sources = global_config
+        .unique_sources()
+        .into_iter()
+        .filter(|(_, source)| {
+            source_filter.is_none_or(|filter| {
+                source.path.contains(filter) || source.source_type.contains(filter)
+            })
+        });
+
+    for (index, source) in sources {
+        println!(
+            "{} Source {}: {} {}",
+            marker(Marker::Source),
+            index + 1,
+            source.source_type,
+            source.path
+        );
+
+        let manifest_dir = GlobalConfig::source_manifest_dir(source);
+        let reachable = match source.source_type.as_str() {
+            "local" => match &manifest_dir {
+                Some(dir) if dir.is_dir() => Ok(()),
+                _ => Err("directory not found".to_string()),
+            },
+            _ => {
+                let token = credentials::source_token(source);
+                match remote::check_source(source, &global_config.remote_probe(), token.as_deref())
+                {
+                    remote::SourceCheck::Reachable | remote::SourceCheck::NotRemote => Ok(()),
+                    remote::SourceCheck::MissingBranch(branch) => {
+                        Err(format!("branch '{}' not found", branch))
+                    }
+                    remote::SourceCheck::Unreachable(reason) => Err(reason),
+                }
+            }
+        };
+        match reachable {
+            Ok(()) => println!("  {} reachable", marker(Marker::Ok)),
+            Err(reason) => {
+                problems += 1;
+                println!("  {} unreachable: {}", marker(Marker::Fail), reason);
+            }
+        }
+
+        let Some(dir) = manifest_dir.filter(|dir| dir.is_dir()) else {
+            continue;
+        };
+        if source.source_type != "local" {
+            println!("  Cached manifests:");
+        }
+        for (tool, path) in manifest_files(&dir, &global_config.manifest_formats)? {
+            match Config::load_from_path(&path) {
+                Ok(_) => println!("  {} {}", marker(Marker::Ok), tool),
+                Err(e) => {
+                    problems += 1;
+                    println!("  {} {}: {}", marker(Marker::Fail), tool, e);
+                }
+            }
+        }
+    }
+
+    if problems > 0 {
+        return Err(crate::error::Error::Config(format!(
+            "{} problem(s) found in manifest sources",
+            problems
+        )));
+    }
+    Ok(())
+}
+
+/// Split `run`/`all`'s trailing command line into the tool name and the arguments passed to
+/// it. Options meant for us come before the tool name, so an unknown one there is an error;
+/// a `--` right after the tool name only separates and is dropped.
+fn split_tool_args(tool_and_args: Vec<String>) -> (Option<String>, Vec<String>) {
+    let mut words = tool_and_args.into_iter();
+    let Some(tool) = words.next() else {
+        return (None, Vec::new());
+    };
+    if tool.starts_with('-') {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::UnknownArgument,
+                format!("unexpected argument '{}' before the tool name", tool),
+            )
+            .exit();
+    }
+
+    let mut args: Vec<String> = words.collect();
+    if args.first().is_some_and(|arg| arg == "--") {
+        args.remove(0);
+    }
+    (Some(tool), args)
+}
+
+/// Ask which tool to run when none was named; outside a terminal the name stays required
+fn pick_tool(
+    global_config: &GlobalConfig,
+    fallback_dir: &std::path::Path,
+) -> crate::error::Result<String> {
+    let interactive = std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+        && std::io::stderr().is_terminal();
+    if !interactive {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "a tool name is required (omit it only on a terminal to pick one interactively)",
+            )
+            .exit();
+    }
+
+    let tools = discoverable_tools(global_config, fallback_dir)?;
+    if tools.is_empty() {
+        return Err(crate::error::Error::NotFound(
+            "No tools found in any manifest source".to_string(),
+        ));
+    }
+    picker::pick("Run which tool?", &tools)?.ok_or(crate::error::Error::Cancelled)
+}
+
+/// Tools provided by every manifest source (cached ones for git and url sources) and
+/// the fallback manifest directory, sorted and deduplicated
+fn discoverable_tools(
+    global_config: &GlobalConfig,
+    fallback_dir: &std::path::Path,
+) -> crate::error::Result<Vec<String>> {
+    let dirs = global_config
+        .unique_sources()
+        .into_iter()
+        .filter_map(|(_, source)| GlobalConfig::source_manifest_dir(source))
+        .chain(std::iter::once(fallback_dir.to_path_buf()));
+
+    let mut tools = std::collections::BTreeSet::new();
+    for dir in dirs.filter(|dir| dir.is_dir()) {
+        for name in manifest_files(&dir, &global_config.manifest_formats)?.into_keys() {
+            // `<tool>.local.<ext>` overrides `<tool>.<ext>`; both are the same tool
+            let name = name
+                .strip_suffix(".local")
+                .map(String::from)
+                .unwrap_or(name);
+            tools.insert(name);
+        }
+    }
+    Ok(tools.into_iter().collect())
+}
+
+/// Manifest files in `dir` with one of the `formats` extensions, keyed by file stem
+fn manifest_files(
+    dir: &std::path::Path,
+    formats: &[String],
+) -> crate::error::Result<std::collections::BTreeMap<String, std::path::PathBuf>> {
+    let mut manifests = std::collections::BTreeMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let known_format = path
+            .extension()
+            .is_some_and(|ext| formats.iter().any(|format| ext == format.as_str()));
+        if path.is_file()
+            && known_format
+            && let Some(name) = path.file_stem()
+        {
+            manifests.insert(name.to_string_lossy().to_string(), path);
+        }
+    }
+    Ok(manifests)
+}
+
+/// Directory whose manifests `manifests info` lists for a source, if any
+fn listing_dir(
+    source: &global_config::ManifestSource,
+    include_cache: bool,
+) -> Option<std::path::PathBuf> {
+    match source.source_type.as_str() {
+        "local" => GlobalConfig::source_manifest_dir(source),
+        "git" | "url" if include_cache => GlobalConfig::source_manifest_dir(source),
+        _ => None,
+    }
+}
+
+/// List the tools cached locally for a remote source
+fn print_cached_manifests(
+    source: &global_config::ManifestSource,
+    cached: Option<Vec<String>>,
+    listing_options: ListingOptions,
+) {
+    let Some(cache_dir) = GlobalConfig::source_manifest_dir(source) else {
+        return;
+    };
+
+    match cached {
+        Some(names) => {
+            println!("  Cached manifests ({}):", cache_dir.display());
+            print_manifest_names(&names, listing_options);
+        }
+        None => println!("  No cached manifests yet"),
+    }
+}
+
+fn print_manifest_names(names: &[String], options: ListingOptions) {
+    if names.is_empty() {
+        println!("    No manifest files found");
+    }
+
+    let mut names: Vec<&String> = names.iter().collect();
+    if options.sort == ManifestSort::Name {
+        names.sort();
+    }
+    let shown = options.limit.unwrap_or(names.len()).min(names.len());
+    for name in &names[..shown] {
+        println!("    - {}", name);
+    }
+    if shown < names.len() {
+        println!("    ... and {} more", names.len() - shown);
+    }
+}
+
+/// Fetch every selected remote source concurrently, printing each source's result as
+/// one block in source order; a failing source doesn't stop the others
+async fn update_manifest_sources(
+    global_config: &GlobalConfig,
+    selector: Option<&str>,
+) -> crate::error::Result<()> {
+    let sources: Vec<global_config::ManifestSource> = match selector {
+        Some(selector) => vec![global_config.find_source(selector)?.clone()],
+        None => global_config
+            .unique_sources()
+            .into_iter()
+            .map(|(_, source)| source.clone())
+            .collect(),
+    };
+
+    let results: Vec<crate::error::Result<Option<String>>> = commands::run_bounded(
+        sources.clone(),
+        global_config.max_concurrency,
+        |source| async move {
+            if source.source_type == "local" {
+                return Ok(None);
+            }
+            let git = global_config.git_client();
+            let formats = global_config.manifest_formats.clone();
+            tokio::task::spawn_blocking(move || {
+                GlobalConfig::update_source(&source, &git, &git.policy, &formats)
+            })
+            .await
+            .context("Manifest source update task failed")?
+            .map(Some)
+        },
+    )
+    .await;
+
+    let mut failed = 0;
+    for (source, result) in sources.iter().zip(results) {
+        let label = format!("{} {}", source.source_type, source.path);
+        match result {
+            Ok(Some(note)) => println!("{} updated: {} ({})", marker(Marker::Ok), label, note),
+            Err(e) => {
+                failed += 1;
+                println!("{} update failed: {}\n  {}", marker(Marker::Fail), label, e);
+            }
+            Ok(None) => println!("{} not a remote source: {}", marker(Marker::Skip), label),
+        }
+    }
+
+    if failed > 0 {
+        return Err(crate::error::Error::Network(format!(
+            "{} manifest source(s) failed to update",
+            failed
+        )));
+    }
+    Ok(())
+}
+
+/// Write the manifest with runtime overrides and interpolation applied, as JSON
+fn dump_resolved_manifest(
+    global_config: &GlobalConfig,
+    config: &Config,
+    path: &std::path::Path,
+) -> crate::error::Result<()> {
+    let install_dir = config.repo_dir(&global_config.get_tools_directory())?;
+    let json = serde_json::to_string_pretty(
+        &config.resolved(&install_dir, &global_config.workspace_dir()?),
+    )
+    .context("Failed to serialize manifest")?;
+
+    if path == std::path::Path::new("-") {
+        println!("{}", json);
+    } else {
+        std::fs::write(path, json + "\n")
+            .with_context(|| format!("Failed to write resolved manifest: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Write a manifest for `name` from the starter at `from` (or a blank one), filling in
+/// its `{{name}}` and `{{url}}` placeholders. The result must parse before it's written.
+fn new_manifest(
+    global_config: &GlobalConfig,
+    manifest_dir: &std::path::Path,
+    name: &str,
+    url: Option<&str>,
+    from: Option<&str>,
+    output: Option<PathBuf>,
+    force: bool,
+) -> crate::error::Result<()> {
+    config::validate_tool_name(name)?;
+
+    let template = match from {
+        Some(location) => remote::fetch_starter(location, &global_config.network_policy())?,
+        None => config::BLANK_MANIFEST.to_string(),
+    };
+
+    // The starter's format carries over to the new manifest
+    let ext = from
+        .and_then(|location| std::path::Path::new(location).extension())
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("jsonc");
+    let path = output.unwrap_or_else(|| manifest_dir.join(format!("{}.{}", name, ext)));
+    if path.exists() && !force {
+        return Err(crate::error::Error::Config(format!(
+            "Manifest already exists: {} (use --force to overwrite)",
+            path.display()
+        )));
+    }
+
+    let manifest = config::render_starter(&template, name, url)?;
+    let config = Config::parse(&manifest, &path)?;
+    if config.repo.name != name {
+        eprintln!(
+            "{} The starter sets the repository name to '{}', not '{}'",
+            marker(Marker::Warn),
+            config.repo.name,
+            name
+        );
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(&path, manifest)
+        .with_context(|| format!("Failed to write manifest: {}", path.display()))?;
+    println!(
+        "{} Created manifest for {}: {}",
+        marker(Marker::Ok),
+        name,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Print a single tool's manifest, resolved through the configured sources
+fn show_tool_manifest(
+    global_config: &GlobalConfig,
+    tool: &str,
+    json: bool,
+) -> crate::error::Result<()> {
+    let manifest_path = global_config
+        .find_tool_manifest(tool)?
+        .map(|found| found.path)
+        .ok_or_else(|| {
+            crate::error::Error::NotFound(format!(
+                "No manifest found for '{}' in any manifest source",
+                tool
+            ))
+        })?;
+    let config = Config::load_from_path(&manifest_path)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&config).context("Failed to serialize manifest")?
+        );
+        return Ok(());
+    }
+
+    println!("Manifest: {}", manifest_path.display());
+    println!("Repository: {}", config.repo.name);
+    println!("  URL: {}", config.repo.url);
+    println!("  Default branch: {}", config.repo.default_branch.name);
+    if let Some(install_dir) = &config.repo.install_dir {
+        println!("  Install dir: {}", install_dir);
+    }
+
+    println!("Dependencies:");
+    if config.dependencies.is_empty() {
+        println!("  (none)");
+    }
+    for dependency in &config.dependencies {
+        println!(
+            "  - {} {} ({})",
+            dependency.name, dependency.version, dependency.url
+        );
+    }
+
+    for (phase, actions) in config.actions.iter_phases() {
+        println!("Actions ({}):", phase);
+        if actions.is_empty() {
+            println!("  (none)");
+        }
+        for action in actions {
+            println!(
+                "  [{}] {}: {}",
+                action.seq_id,
+                action.description,
+                action.invocation()?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Probe each selected source and report whether it can be reached
+fn check_manifest_sources(
+    global_config: &GlobalConfig,
+    selector: Option<&str>,
+) -> crate::error::Result<()> {
+    let sources = match selector {
+        Some(selector) => vec![global_config.find_source(selector)?],
+        None => global_config.manifest_sources.iter().collect(),
+    };
+
+    let mut unreachable = 0;
+    for source in sources {
+        let token = credentials::source_token(source);
+        let label = format!("{} {}", source.source_type, source.path);
+        match remote::check_source(source, &global_config.remote_probe(), token.as_deref()) {
+            remote::SourceCheck::Reachable => {
+                println!("{} reachable: {}", marker(Marker::Ok), label)
+            }
+            remote::SourceCheck::MissingBranch(branch) => {
+                unreachable += 1;
+                println!(
+                    "{} branch '{}' not found: {}",
+                    marker(Marker::Fail),
+                    branch,
+                    label
+                );
+            }
+            remote::SourceCheck::Unreachable(reason) => {
+                unreachable += 1;
+                println!(
+                    "{} unreachable: {} ({})",
+                    marker(Marker::Fail),
+                    label,
+                    reason
+                );
+            }
+            remote::SourceCheck::NotRemote => {
+                println!("{} not a remote source: {}", marker(Marker::Skip), label)
+            }
+        }
+    }
+
+    if unreachable > 0 {
+        return Err(crate::error::Error::Network(format!(
+            "{} manifest source(s) failed the check",
+            unreachable
+        )));
+    }
+    Ok(())
+}
+
+/// Compare a source's manifests against the snapshot recorded by the previous
+/// diff, print the differences, then record the current state as the new snapshot
+fn diff_manifest_source(global_config: &GlobalConfig, selector: &str) -> crate::error::Result<()> {
+    let source = global_config.find_source(selector)?;
+    let manifest_dir = GlobalConfig::source_manifest_dir(source).ok_or_else(|| {
+        crate::error::Error::Config(format!("Unknown source type: {}", source.source_type))
+    })?;
+    let snapshot_dir = GlobalConfig::source_snapshot_dir(source);
+
+    // Each tool's manifest file name and contents
+    let read_manifests = |dir: &std::path::Path| -> crate::error::Result<_> {
+        let mut manifests = std::collections::BTreeMap::new();
+        if dir.exists() {
+            for (name, path) in manifest_files(dir, &global_config.manifest_formats)? {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                let content = std::fs::read_to_string(&path)?;
+                manifests.insert(name, (file_name.to_string(), content));
+            }
+        }
+        Ok(manifests)
+    };
+    let current = read_manifests(&manifest_dir)?;
+    let previous = read_manifests(&snapshot_dir)?;
+
+    println!(
+        "Manifest changes for {} {}:",
+        source.source_type, source.path
+    );
+
+    let names: std::collections::BTreeSet<&String> =
+        current.keys().chain(previous.keys()).collect();
+    let mut changes = 0;
+    for name in names {
+        match (previous.get(name), current.get(name)) {
+            (None, Some(_)) => println!("  + new: {}", name),
+            (Some(_), None) => println!("  - removed: {}", name),
+            (Some((old_file, old)), Some((new_file, new))) if old != new => {
+                println!("  ~ changed: {}", name);
+                let diff = similar::TextDiff::from_lines(old, new);
+                print!(
+                    "{}",
+                    diff.unified_diff()
+                        .header(&format!("a/{}", old_file), &format!("b/{}", new_file))
+                );
+            }
+            _ => continue,
+        }
+        changes += 1;
+    }
+
+    if changes == 0 {
+        println!("  No changes since the last diff");
+    }
+
+    // Record the current state for the next diff
+    if snapshot_dir.exists() {
+        std::fs::remove_dir_all(&snapshot_dir)?;
+    }
+    std::fs::create_dir_all(&snapshot_dir)?;
+    for (file_name, content) in current.values() {
+        std::fs::write(snapshot_dir.join(file_name), content)?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -377,12 +1926,16 @@ mod tests {
             source_type: "local".to_string(),
             path: "/test/path".to_string(),
             branch: None,
+            subdir: None,
+            auth: None,
             auto_update: false,
         });
         config.manifest_sources.push(ManifestSource {
             source_type: "git".to_string(),
             path: "https://github.com/example/repo.git".to_string(),
             branch: Some("main".to_string()),
+            subdir: None,
+            auth: None,
             auto_update: true,
         });
 
@@ -406,10 +1959,12 @@ mod tests {
             source_type: "local".to_string(),
             path: manifest_dir.to_string_lossy().to_string(),
             branch: None,
+            subdir: None,
+            auth: None,
             auto_update: false,
         });
 
-        let result = show_manifest_info(&config, &None).await;
+        let result = show_manifest_info(&config, &None, false, ListingOptions::default()).await;
         assert!(result.is_ok());
     }
 
@@ -421,10 +1976,12 @@ mod tests {
             source_type: "local".to_string(),
             path: "/nonexistent/path".to_string(),
             branch: None,
+            subdir: None,
+            auth: None,
             auto_update: false,
         });
 
-        let result = show_manifest_info(&config, &None).await;
+        let result = show_manifest_info(&config, &None, false, ListingOptions::default()).await;
         assert!(result.is_ok()); // Should not fail, just show warning
     }
 
@@ -436,17 +1993,21 @@ mod tests {
             source_type: "local".to_string(),
             path: "/test/local".to_string(),
             branch: None,
+            subdir: None,
+            auth: None,
             auto_update: false,
         });
         config.manifest_sources.push(ManifestSource {
             source_type: "git".to_string(),
             path: "https://github.com/example/repo.git".to_string(),
             branch: None,
+            subdir: None,
+            auth: None,
             auto_update: true,
         });
 
         let filter = Some("github".to_string());
-        let result = show_manifest_info(&config, &filter).await;
+        let result = show_manifest_info(&config, &filter, false, ListingOptions::default()).await;
         assert!(result.is_ok());
     }
 
@@ -461,7 +2022,10 @@ mod tests {
                 "local".to_string(),
                 manifest_dir.to_string_lossy().to_string(),
                 None,
+                None,
                 true,
+                None,
+                None,
             );
 
             assert!(result.is_ok());
@@ -475,7 +2039,10 @@ mod tests {
                 "local".to_string(),
                 "/nonexistent/path".to_string(),
                 None,
+                None,
                 true,
+                None,
+                None,
             );
 
             assert!(result.is_err());
@@ -495,7 +2062,10 @@ mod tests {
                 "git".to_string(),
                 "https://github.com/example/repo.git".to_string(),
                 Some("main".to_string()),
+                None,
                 true,
+                None,
+                None,
             );
 
             assert!(result.is_ok());
@@ -505,8 +2075,15 @@ mod tests {
     #[test]
     fn test_add_manifest_source_git_invalid() {
         with_test_config(|| {
-            let result =
-                add_manifest_source("git".to_string(), "invalid-url".to_string(), None, true);
+            let result = add_manifest_source(
+                "git".to_string(),
+                "invalid-url".to_string(),
+                None,
+                None,
+                true,
+                None,
+                None,
+            );
 
             assert!(result.is_err());
             assert!(
@@ -525,7 +2102,10 @@ mod tests {
                 "url".to_string(),
                 "https://example.com/manifests".to_string(),
                 None,
+                None,
                 false,
+                None,
+                None,
             );
 
             if let Err(ref e) = result {
@@ -542,7 +2122,10 @@ mod tests {
                 "url".to_string(),
                 "ftp://example.com/manifests".to_string(),
                 None,
+                None,
                 true,
+                None,
+                None,
             );
 
             assert!(result.is_err());
@@ -554,4 +2137,62 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn test_discoverable_tools_merges_sources_and_fallback() {
+        let temp_dir = tempdir().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        let fallback_dir = temp_dir.path().join("fallback");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::create_dir_all(&fallback_dir).unwrap();
+        fs::write(source_dir.join("fd.jsonc"), "{}").unwrap();
+        fs::write(source_dir.join("fd.local.jsonc"), "{}").unwrap();
+        fs::write(source_dir.join("notes.txt"), "").unwrap();
+        fs::write(fallback_dir.join("rg.toml"), "").unwrap();
+        fs::write(fallback_dir.join("fd.jsonc"), "{}").unwrap();
+
+        let mut config = GlobalConfig::default();
+        config.manifest_sources.clear();
+        config.manifest_sources.push(ManifestSource {
+            source_type: "local".to_string(),
+            path: source_dir.to_string_lossy().to_string(),
+            branch: None,
+            subdir: None,
+            auth: None,
+            auto_update: false,
+        });
+
+        let tools = discoverable_tools(&config, &fallback_dir).unwrap();
+        assert_eq!(tools, vec!["fd".to_string(), "rg".to_string()]);
+    }
+
+    #[test]
+    fn test_run_passes_everything_after_the_tool_through() {
+        let parse = |argv: &[&str]| match Cli::try_parse_from(argv).unwrap().command {
+            Some(Commands::Run {
+                spawn,
+                tool_and_args,
+                ..
+            }) => (spawn, split_tool_args(tool_and_args)),
+            _ => panic!("expected the run command"),
+        };
+        let words = |words: &[&str]| {
+            words
+                .iter()
+                .map(|word| word.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let (spawn, (tool, args)) = parse(&["tm", "run", "--spawn", "v", "--verbose", "-q", "x"]);
+        assert!(spawn);
+        assert_eq!(tool.as_deref(), Some("v"));
+        assert_eq!(args, words(&["--verbose", "-q", "x"]));
+
+        let (spawn, (_, args)) = parse(&["tm", "run", "v", "--spawn", "--", "x"]);
+        assert!(!spawn);
+        assert_eq!(args, words(&["--spawn", "--", "x"]));
+
+        let (_, (_, args)) = parse(&["tm", "run", "v", "--", "--spawn"]);
+        assert_eq!(args, words(&["--spawn"]));
+    }
 }
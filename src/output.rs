@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Status markers prefixed to console messages
+#[derive(Debug, Clone, Copy)]
+pub enum Marker {
+    Ok,
+    Done,
+    Warn,
+    Fail,
+    Skip,
+    Source,
+}
+
+/// Switch markers to their ASCII form for the rest of the process
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// Text for a marker, honoring plain mode
+pub fn marker(marker: Marker) -> &'static str {
+    if PLAIN.load(Ordering::Relaxed) {
+        return match marker {
+            Marker::Ok | Marker::Done => "[OK]",
+            Marker::Warn => "[WARN]",
+            Marker::Fail => "[FAIL]",
+            Marker::Skip => "[SKIP]",
+            Marker::Source => "[SOURCE]",
+        };
+    }
+
+    // Emoji with a variation selector render two columns wide, so they carry an extra space
+    match marker {
+        Marker::Ok => "✅",
+        Marker::Done => "✓",
+        Marker::Warn => "⚠️ ",
+        Marker::Fail => "❌",
+        Marker::Skip => "⏭️ ",
+        Marker::Source => "📁",
+    }
+}
+
+/// Destination for the messages commands print
+pub trait Output: Send + Sync {
+    /// A progress or result line (stdout on the console)
+    fn line(&self, text: &str);
+    /// A warning, trace or debug line (stderr on the console)
+    fn diagnostic(&self, text: &str);
+}
+
+/// Prints lines to stdout and diagnostics to stderr
+pub struct Console;
+
+impl Output for Console {
+    fn line(&self, text: &str) {
+        println!("{}", text);
+    }
+
+    fn diagnostic(&self, text: &str) {
+        eprintln!("{}", text);
+    }
+}
+
+/// Shows lines as the message of one progress bar, printing diagnostics above the bars
+pub struct ProgressLine(pub indicatif::ProgressBar);
+
+impl Output for ProgressLine {
+    fn line(&self, text: &str) {
+        self.0
+            .set_message(text.lines().next().unwrap_or_default().to_string());
+    }
+
+    fn diagnostic(&self, text: &str) {
+        self.0.println(text);
+    }
+}
+
+/// Keeps messages in memory so tests can assert on them
+#[cfg(test)]
+#[derive(Default)]
+pub struct Captured {
+    lines: std::sync::Mutex<Vec<String>>,
+    diagnostics: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl Captured {
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().clone()
+    }
+
+    pub fn diagnostics(&self) -> Vec<String> {
+        self.diagnostics.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl Output for Captured {
+    fn line(&self, text: &str) {
+        self.lines.lock().unwrap().push(text.to_string());
+    }
+
+    fn diagnostic(&self, text: &str) {
+        self.diagnostics.lock().unwrap().push(text.to_string());
+    }
+}
+
+/// Shared handle to an output sink; the console unless replaced
+#[derive(Clone)]
+pub struct Sink(Arc<dyn Output>);
+
+impl Sink {
+    pub fn new(output: Arc<dyn Output>) -> Self {
+        Self(output)
+    }
+}
+
+impl Default for Sink {
+    fn default() -> Self {
+        Self(Arc::new(Console))
+    }
+}
+
+impl std::fmt::Debug for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Sink")
+    }
+}
+
+impl std::ops::Deref for Sink {
+    type Target = dyn Output;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
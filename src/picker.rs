@@ -0,0 +1,85 @@
+use crate::error::Result;
+use console::{Key, Term};
+
+/// Most candidates listed below the query line at once
+const MAX_SHOWN: usize = 10;
+
+/// Whether the characters of `query` appear in `candidate` in order, ignoring case
+pub fn fuzzy_matches(query: &str, candidate: &str) -> bool {
+    let mut candidate = candidate.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|wanted| candidate.any(|c| c == wanted))
+}
+
+/// Let the user pick one of `items` on the terminal: typing filters the list, arrow
+/// keys move the selection, Enter picks and Esc or Ctrl-C cancels (`None`)
+pub fn pick(prompt: &str, items: &[String]) -> Result<Option<String>> {
+    let term = Term::stderr();
+    term.hide_cursor()?;
+    let picked = pick_on(&term, prompt, items);
+    term.show_cursor()?;
+    picked
+}
+
+fn pick_on(term: &Term, prompt: &str, items: &[String]) -> Result<Option<String>> {
+    let mut query = String::new();
+    let mut selected = 0;
+    let mut drawn = 0;
+
+    loop {
+        let matches: Vec<&String> = items
+            .iter()
+            .filter(|item| fuzzy_matches(&query, item))
+            .take(MAX_SHOWN)
+            .collect();
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        term.clear_last_lines(drawn)?;
+        term.write_line(&format!("{} {}", prompt, query))?;
+        for (index, item) in matches.iter().enumerate() {
+            let cursor = if index == selected { ">" } else { " " };
+            term.write_line(&format!("{} {}", cursor, item))?;
+        }
+        if matches.is_empty() {
+            term.write_line("  (no matching tools)")?;
+        }
+        drawn = 1 + matches.len().max(1);
+
+        match term.read_key_raw()? {
+            Key::Enter => {
+                if let Some(item) = matches.get(selected) {
+                    term.clear_last_lines(drawn)?;
+                    return Ok(Some(item.to_string()));
+                }
+            }
+            Key::Escape | Key::CtrlC => {
+                term.clear_last_lines(drawn)?;
+                return Ok(None);
+            }
+            Key::ArrowUp => selected = selected.saturating_sub(1),
+            Key::ArrowDown if selected + 1 < matches.len() => selected += 1,
+            Key::Backspace => {
+                query.pop();
+            }
+            Key::Char(c) if !c.is_control() => query.push(c),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_matches_subsequence_ignoring_case() {
+        assert!(fuzzy_matches("", "ripgrep"));
+        assert!(fuzzy_matches("rg", "ripgrep"));
+        assert!(fuzzy_matches("RiPg", "ripgrep"));
+        assert!(fuzzy_matches("fd", "fd-find"));
+        assert!(!fuzzy_matches("gr", "rg"));
+        assert!(!fuzzy_matches("bat", "ripgrep"));
+    }
+}
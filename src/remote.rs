@@ -0,0 +1,1056 @@
+use crate::error::{Error, Result};
+use crate::global_config::ManifestSource;
+use crate::output::{Marker, marker};
+use anyhow::Context;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Timeouts and retries applied to git and url fetches
+#[derive(Debug, Clone)]
+pub struct NetworkPolicy {
+    /// Give up connecting, or on a transfer that stalls, after this long
+    pub timeout: Duration,
+    /// Further attempts after a transient failure
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each one after
+    pub backoff: Duration,
+}
+
+impl Default for NetworkPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            retries: 2,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Why a single network attempt failed
+struct AttemptError {
+    message: String,
+    /// Worth retrying: the remote was unreachable, timed out or had a server error
+    transient: bool,
+}
+
+impl AttemptError {
+    fn transient(message: String) -> Self {
+        Self {
+            message,
+            transient: true,
+        }
+    }
+
+    fn permanent(message: String) -> Self {
+        Self {
+            message,
+            transient: false,
+        }
+    }
+}
+
+/// Run `attempt`, retrying transient failures with exponential backoff
+fn with_retries<T>(
+    policy: &NetworkPolicy,
+    mut attempt: impl FnMut() -> std::result::Result<T, AttemptError>,
+) -> Result<T> {
+    let attempts = policy.retries + 1;
+    let mut delay = policy.backoff;
+    for number in 1..=attempts {
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(e) if !e.transient => return Err(Error::Network(e.message)),
+            Err(e) if number == attempts => {
+                return Err(Error::Network(if attempts > 1 {
+                    format!("{} (gave up after {} attempts)", e.message, attempts)
+                } else {
+                    e.message
+                }));
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} {}; retrying in {}ms (attempt {} of {})",
+                    marker(Marker::Warn),
+                    e.message,
+                    delay.as_millis(),
+                    number + 1,
+                    attempts
+                );
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+        }
+    }
+    unreachable!("the last attempt always returns")
+}
+
+/// Fetch `<base_url>/<tool_name>.<ext>` into `cache_dir`, trying the extensions in
+/// `formats` in order.
+///
+/// A cached copy is used as-is unless `refresh` is set. When the download fails
+/// because the source is unreachable, a cached copy (if any) is used instead and
+/// a warning is printed; the same goes for a tool the source stopped publishing.
+/// Returns `None` when the source does not have the tool and nothing is cached.
+pub fn fetch_url_manifest(
+    base_url: &str,
+    tool_name: &str,
+    cache_dir: &Path,
+    formats: &[String],
+    refresh: bool,
+    token: Option<&str>,
+    policy: &NetworkPolicy,
+) -> Result<Option<PathBuf>> {
+    let file_names: Vec<String> = formats
+        .iter()
+        .map(|ext| format!("{}.{}", tool_name, ext))
+        .collect();
+    if !refresh
+        && let Some(cached) = file_names
+            .iter()
+            .map(|name| cache_dir.join(name))
+            .find(|path| path.is_file())
+    {
+        return Ok(Some(cached));
+    }
+
+    for file_name in &file_names {
+        let fetched = fetch_file(
+            base_url,
+            file_name,
+            &cache_dir.join(file_name),
+            refresh,
+            token,
+            policy,
+        )?;
+        if fetched.is_some() {
+            return Ok(fetched);
+        }
+    }
+    Ok(None)
+}
+
+/// Fetch `<base_url>/index.json`, a JSON array naming the tools a url source provides.
+///
+/// Cached at `cached` like manifests. Returns `None` when the source publishes no
+/// index, in which case tools can only be fetched on demand by name.
+pub fn fetch_url_index(
+    base_url: &str,
+    cached: &Path,
+    refresh: bool,
+    token: Option<&str>,
+    policy: &NetworkPolicy,
+) -> Result<Option<Vec<String>>> {
+    let Some(path) = fetch_file(base_url, "index.json", cached, refresh, token, policy)? else {
+        return Ok(None);
+    };
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read manifest index: {}", path.display()))?;
+    let tools: Vec<String> = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid manifest index for {}", base_url))?;
+    Ok(Some(tools))
+}
+
+/// Read a starter manifest from a local path or a `file://`, `http://` or `https://` URL.
+///
+/// Remote starters are never cached: each use downloads the starter again, and a failed
+/// download is an error rather than a reason to reuse an older copy.
+pub fn fetch_starter(location: &str, policy: &NetworkPolicy) -> Result<String> {
+    let not_found = || Error::NotFound(format!("Starter manifest not found: {}", location));
+    let read = |path: &Path| -> Result<String> {
+        Ok(std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read starter manifest: {}", path.display()))?)
+    };
+
+    let path = match location.split_once("://") {
+        Some(("file", path)) => PathBuf::from(path),
+        Some(_) => {
+            if location.ends_with('/') {
+                return Err(Error::Config(format!(
+                    "Starter URL must name a file: {}",
+                    location
+                )));
+            }
+            // A freshly created private directory, removed (with any partial download) on drop
+            let scratch_dir = tempfile::Builder::new()
+                .prefix("tool-meister-starter-")
+                .tempdir()
+                .context("Failed to create a temporary directory for the starter manifest")?;
+            let scratch = scratch_dir.path().join("starter");
+            return match download(location, &scratch, None, policy) {
+                Ok(true) => read(&scratch),
+                Ok(false) => Err(not_found()),
+                Err(e) => Err(e),
+            };
+        }
+        None => PathBuf::from(location),
+    };
+    if !path.is_file() {
+        return Err(not_found());
+    }
+    read(&path)
+}
+
+/// Fetch `<base_url>/<file_name>` to `cached`, falling back to a stale copy when unreachable
+fn fetch_file(
+    base_url: &str,
+    file_name: &str,
+    cached: &Path,
+    refresh: bool,
+    token: Option<&str>,
+    policy: &NetworkPolicy,
+) -> Result<Option<PathBuf>> {
+    if cached.exists() && !refresh {
+        return Ok(Some(cached.to_path_buf()));
+    }
+
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), file_name);
+    let fetched = match url.strip_prefix("file://") {
+        Some(path) => copy_local(Path::new(path), cached).map(|copied| copied.is_some()),
+        None => download(&url, cached, token, policy),
+    };
+    match fetched {
+        Ok(true) => Ok(Some(cached.to_path_buf())),
+        // A file that vanished upstream may be a publishing hiccup; keep serving our copy
+        Ok(false) if cached.exists() => {
+            eprintln!(
+                "{} {} is no longer published; using the cached copy: {}",
+                marker(Marker::Warn),
+                url,
+                cached.display()
+            );
+            Ok(Some(cached.to_path_buf()))
+        }
+        Ok(false) => Ok(None),
+        Err(e) if cached.exists() => {
+            eprintln!("{} {}", marker(Marker::Warn), e);
+            eprintln!(
+                "{} Using stale cached copy: {}",
+                marker(Marker::Warn),
+                cached.display()
+            );
+            Ok(Some(cached.to_path_buf()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Copy a `file://` file into the cache, returning `None` if it doesn't exist
+fn copy_local(source: &Path, dest: &Path) -> Result<Option<PathBuf>> {
+    if !source.is_file() {
+        return Ok(None);
+    }
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache directory: {}", parent.display()))?;
+    }
+    std::fs::copy(source, dest).with_context(|| format!("Failed to copy {}", source.display()))?;
+    Ok(Some(dest.to_path_buf()))
+}
+
+/// Download `url` to `dest`, returning `false` if the server reports 404.
+///
+/// The file is written to a temporary path first so a failed download never
+/// clobbers an existing cached copy. A `token` is sent as a bearer `Authorization`
+/// header, passed to curl on stdin to keep it out of the process list. Connection
+/// failures, stalls and server errors are retried according to `policy`.
+fn download(url: &str, dest: &Path, token: Option<&str>, policy: &NetworkPolicy) -> Result<bool> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache directory: {}", parent.display()))?;
+    }
+
+    let mut tmp_name = dest.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".part");
+    let tmp = dest.with_file_name(tmp_name);
+
+    let status = with_retries(policy, || {
+        let result = curl_download(url, &tmp, token, policy);
+        if !matches!(result.as_deref(), Ok("200")) {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        match result?.as_str() {
+            status @ ("200" | "404") => Ok(status.to_string()),
+            status => {
+                let message = format!("Failed to fetch {}: HTTP status {}", url, status);
+                // Server errors and throttling may clear up; other statuses won't
+                if status.starts_with('5') || status == "408" || status == "429" {
+                    Err(AttemptError::transient(message))
+                } else {
+                    Err(AttemptError::permanent(message))
+                }
+            }
+        }
+    })?;
+
+    if status == "404" {
+        return Ok(false);
+    }
+    std::fs::rename(&tmp, dest)?;
+    Ok(true)
+}
+
+/// One curl request saving the body to `tmp`, returning the HTTP status
+fn curl_download(
+    url: &str,
+    tmp: &Path,
+    token: Option<&str>,
+    policy: &NetworkPolicy,
+) -> std::result::Result<String, AttemptError> {
+    let timeout = policy.timeout.as_secs().max(1).to_string();
+    let mut curl = Command::new("curl");
+    curl.args(["-sS", "-L", "-w", "%{http_code}"])
+        // A transfer slower than 1 byte/s for the whole timeout counts as stalled
+        .args([
+            "--connect-timeout",
+            &timeout,
+            "--speed-limit",
+            "1",
+            "--speed-time",
+            &timeout,
+        ])
+        .arg("-o")
+        .arg(tmp)
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if token.is_some() {
+        curl.args(["-H", "@-"]).stdin(Stdio::piped());
+    }
+
+    let permanent =
+        |e: std::io::Error, what: &str| AttemptError::permanent(format!("{}: {}", what, e));
+    let mut child = curl
+        .spawn()
+        .map_err(|e| permanent(e, "Failed to run curl"))?;
+    if let (Some(token), Some(mut stdin)) = (token, child.stdin.take()) {
+        writeln!(stdin, "Authorization: Bearer {}", token)
+            .map_err(|e| permanent(e, "Failed to pass credentials to curl"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| permanent(e, "Failed to run curl"))?;
+
+    if !output.status.success() {
+        let message = format!(
+            "Failed to fetch {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Err(if curl_failure_is_transient(output.status.code()) {
+            AttemptError::transient(message)
+        } else {
+            AttemptError::permanent(message)
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether a curl exit code means the connection failed or timed out, which a retry may
+/// fix; bad URLs, certificate problems and the like fail the same way every time
+fn curl_failure_is_transient(code: Option<i32>) -> bool {
+    // 5/6: proxy or host not resolved, 7: couldn't connect, 18: partial transfer,
+    // 28: timed out, 35: TLS handshake failed, 52: empty reply, 55/56: send/receive failed
+    matches!(code, Some(5 | 6 | 7 | 18 | 28 | 35 | 52 | 55 | 56))
+}
+
+/// Network calls used to check remote sources without cloning or downloading them
+pub trait RemoteProbe {
+    /// Whether `branch` exists on the git remote at `url`; errors when it can't be reached
+    fn git_branch_exists(&self, url: &str, branch: Option<&str>) -> Result<bool>;
+    /// HTTP status of a HEAD request for `url`; errors when it can't be reached
+    fn http_head(&self, url: &str, token: Option<&str>) -> Result<u16>;
+}
+
+/// Probe using the `git` and `curl` executables
+pub struct SystemProbe {
+    pub policy: NetworkPolicy,
+    /// `GIT_SSH_COMMAND` for SSH remotes, as for [`SystemGit`]
+    pub ssh_command: Option<String>,
+}
+
+impl RemoteProbe for SystemProbe {
+    fn git_branch_exists(&self, url: &str, branch: Option<&str>) -> Result<bool> {
+        let mut git = git_command(&self.policy, self.ssh_command.as_deref());
+        git.arg("ls-remote");
+        match branch {
+            Some(branch) => git.args(["--heads", url, branch]),
+            None => git.args([url, "HEAD"]),
+        };
+
+        let output = git.output().context("Failed to run git")?;
+        if !output.status.success() {
+            return Err(Error::Network(format!(
+                "git ls-remote {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(!output.stdout.is_empty())
+    }
+
+    fn http_head(&self, url: &str, token: Option<&str>) -> Result<u16> {
+        let timeout = self.policy.timeout.as_secs().max(1).to_string();
+        let mut curl = Command::new("curl");
+        curl.args(["-sS", "-I", "-L", "-o", "/dev/null", "-w", "%{http_code}"])
+            .args(["--connect-timeout", &timeout, "--max-time", &timeout])
+            .arg(url)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if token.is_some() {
+            curl.args(["-H", "@-"]).stdin(Stdio::piped());
+        }
+
+        let mut child = curl.spawn().context("Failed to run curl")?;
+        if let (Some(token), Some(mut stdin)) = (token, child.stdin.take()) {
+            writeln!(stdin, "Authorization: Bearer {}", token)
+                .context("Failed to pass credentials to curl")?;
+        }
+        let output = child.wait_with_output().context("Failed to run curl")?;
+
+        if !output.status.success() {
+            return Err(Error::Network(format!(
+                "Failed to reach {}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .map_err(|_| Error::Network(format!("Failed to reach {}: no HTTP status", url)))
+    }
+}
+
+/// Git operations that write to disk, behind a trait so callers can be tested without a remote
+pub trait GitClient {
+    /// Clone `url` (at `branch`, or the remote's default) into `dest`
+    fn clone_repo(&self, url: &str, branch: Option<&str>, dest: &Path) -> Result<()>;
+    /// Fast-forward the clone at `dest` to its upstream
+    fn pull(&self, dest: &Path) -> Result<()>;
+}
+
+/// Whether `url` is an SSH git URL: `ssh://...` or the scp-like `user@host:path`
+pub fn is_ssh_url(url: &str) -> bool {
+    if url.starts_with("ssh://") {
+        return true;
+    }
+    !url.contains("://")
+        && url.split_once(':').is_some_and(|(host, path)| {
+            host.contains('@') && !host.contains('/') && !path.is_empty()
+        })
+}
+
+/// Git client using the `git` executable
+pub struct SystemGit {
+    pub policy: NetworkPolicy,
+    /// `GIT_SSH_COMMAND` for SSH remotes; unset uses `ssh` with the policy's connect
+    /// timeout, unless `GIT_SSH_COMMAND` or `GIT_SSH` is already in the environment
+    pub ssh_command: Option<String>,
+}
+
+/// A `git` command that doesn't prompt and aborts connections and transfers stalled past
+/// the policy's timeout
+fn git_command(policy: &NetworkPolicy, ssh_command: Option<&str>) -> Command {
+    let timeout = policy.timeout.as_secs().max(1).to_string();
+    let mut git = Command::new("git");
+    git.env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_HTTP_LOW_SPEED_LIMIT", "1")
+        .env("GIT_HTTP_LOW_SPEED_TIME", &timeout);
+    match ssh_command {
+        Some(ssh_command) => {
+            git.env("GIT_SSH_COMMAND", ssh_command);
+        }
+        None if std::env::var_os("GIT_SSH_COMMAND").is_none()
+            && std::env::var_os("GIT_SSH").is_none() =>
+        {
+            git.env(
+                "GIT_SSH_COMMAND",
+                format!("ssh -o ConnectTimeout={}", timeout),
+            );
+        }
+        None => {}
+    }
+    git
+}
+
+impl SystemGit {
+    fn command(&self) -> Command {
+        git_command(&self.policy, self.ssh_command.as_deref())
+    }
+
+    fn run(mut git: Command, what: &str) -> std::result::Result<(), AttemptError> {
+        let output = git
+            .output()
+            .map_err(|e| AttemptError::permanent(format!("Failed to run git: {}", e)))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = format!("{} failed: {}", what, stderr.trim());
+            return Err(if git_failure_is_transient(&stderr) {
+                AttemptError::transient(message)
+            } else {
+                AttemptError::permanent(message)
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Whether git's error output points at a connection failure or timeout, which a retry
+/// may fix; missing repositories, rejected credentials and the like won't
+fn git_failure_is_transient(stderr: &str) -> bool {
+    const TRANSIENT: [&str; 11] = [
+        "could not resolve host",
+        "connection refused",
+        "connection timed out",
+        "connection reset",
+        "failed to connect",
+        "operation timed out",
+        "operation too slow",
+        "the remote end hung up unexpectedly",
+        "early eof",
+        "rpc failed",
+        "the requested url returned error: 5",
+    ];
+    let stderr = stderr.to_lowercase();
+    TRANSIENT.iter().any(|marker| stderr.contains(marker))
+}
+
+impl GitClient for SystemGit {
+    fn clone_repo(&self, url: &str, branch: Option<&str>, dest: &Path) -> Result<()> {
+        with_retries(&self.policy, || {
+            let mut git = self.command();
+            git.args(["clone", "--quiet"]);
+            if let Some(branch) = branch {
+                git.args(["--branch", branch]);
+            }
+            git.arg(url).arg(dest);
+
+            let result = Self::run(git, &format!("git clone {}", url));
+            if result.is_err() {
+                // A partial clone would make the retry fail on a non-empty directory
+                let _ = std::fs::remove_dir_all(dest);
+            }
+            result
+        })
+    }
+
+    fn pull(&self, dest: &Path) -> Result<()> {
+        with_retries(&self.policy, || {
+            let mut git = self.command();
+            git.arg("-C")
+                .arg(dest)
+                .args(["pull", "--quiet", "--ff-only"]);
+            Self::run(git, &format!("git pull in {}", dest.display()))
+        })
+    }
+}
+
+/// Outcome of checking a manifest source
+#[derive(Debug, PartialEq)]
+pub enum SourceCheck {
+    Reachable,
+    /// The git remote answered but doesn't have the configured branch
+    MissingBranch(String),
+    Unreachable(String),
+    /// Local sources have nothing to reach
+    NotRemote,
+}
+
+/// Check that a git or url source is reachable (and a git source's branch exists).
+///
+/// Git sources are checked with `git ls-remote`; url sources with a HEAD request for
+/// their `index.json`, where a 404 still proves the server is there.
+pub fn check_source(
+    source: &ManifestSource,
+    probe: &dyn RemoteProbe,
+    token: Option<&str>,
+) -> SourceCheck {
+    match source.source_type.as_str() {
+        "git" => match probe.git_branch_exists(&source.path, source.branch.as_deref()) {
+            Ok(true) => SourceCheck::Reachable,
+            Ok(false) => SourceCheck::MissingBranch(source.branch.clone().unwrap_or_default()),
+            Err(e) => SourceCheck::Unreachable(e.to_string()),
+        },
+        "url" => {
+            let url = format!("{}/index.json", source.path.trim_end_matches('/'));
+            match probe.http_head(&url, token) {
+                Ok(200..=399 | 404) => SourceCheck::Reachable,
+                Ok(status) => SourceCheck::Unreachable(format!("HTTP status {}", status)),
+                Err(e) => SourceCheck::Unreachable(e.to_string()),
+            }
+        }
+        _ => SourceCheck::NotRemote,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    // Nothing listens on port 1, so connecting fails immediately
+    const UNREACHABLE_URL: &str = "http://127.0.0.1:1/manifests";
+
+    fn formats() -> Vec<String> {
+        vec!["jsonc".to_string()]
+    }
+
+    fn quick_policy(retries: u32) -> NetworkPolicy {
+        NetworkPolicy {
+            timeout: Duration::from_secs(1),
+            retries,
+            backoff: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn test_is_ssh_url() {
+        assert!(is_ssh_url("git@github.com:example/repo.git"));
+        assert!(is_ssh_url("deploy@git.corp.example:tools/manifests.git"));
+        assert!(is_ssh_url("ssh://git@git.corp.example:2222/tools.git"));
+        assert!(!is_ssh_url("https://user@github.com/example/repo.git"));
+        assert!(!is_ssh_url("/srv/repos/manifests"));
+        assert!(!is_ssh_url("git@github.com:"));
+    }
+
+    #[test]
+    fn test_system_git_passes_ssh_command() {
+        let ssh_env = |git: &SystemGit| {
+            git.command()
+                .get_envs()
+                .find(|(key, _)| *key == "GIT_SSH_COMMAND")
+                .and_then(|(_, value)| value.map(|v| v.to_string_lossy().to_string()))
+        };
+
+        let default_git = SystemGit {
+            policy: quick_policy(0),
+            ssh_command: None,
+        };
+        if std::env::var_os("GIT_SSH_COMMAND").is_none() && std::env::var_os("GIT_SSH").is_none() {
+            assert_eq!(
+                ssh_env(&default_git).as_deref(),
+                Some("ssh -o ConnectTimeout=1")
+            );
+        }
+
+        let configured = SystemGit {
+            policy: quick_policy(0),
+            ssh_command: Some("ssh -i ~/.ssh/corp_key".to_string()),
+        };
+        assert_eq!(
+            ssh_env(&configured).as_deref(),
+            Some("ssh -i ~/.ssh/corp_key")
+        );
+    }
+
+    #[test]
+    fn test_fetch_url_manifest_falls_back_to_cache() {
+        let cache_dir = tempdir().unwrap();
+        let cached = cache_dir.path().join("tool.jsonc");
+        fs::write(&cached, "{}").unwrap();
+
+        let result = fetch_url_manifest(
+            UNREACHABLE_URL,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(0),
+        )
+        .unwrap();
+        assert_eq!(result, Some(cached.clone()));
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_fetch_url_manifest_errors_without_cache() {
+        let cache_dir = tempdir().unwrap();
+
+        let result = fetch_url_manifest(
+            UNREACHABLE_URL,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(0),
+        );
+        assert!(matches!(result, Err(Error::Network(_))));
+        assert!(!cache_dir.path().join("tool.jsonc.part").exists());
+    }
+
+    #[test]
+    fn test_fetch_url_manifest_from_file_url() {
+        let source_dir = tempdir().unwrap();
+        let cache_dir = tempdir().unwrap();
+        fs::write(source_dir.path().join("tool.jsonc"), "{\"a\": 1}").unwrap();
+        let base_url = format!("file://{}", source_dir.path().display());
+
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(0),
+        )
+        .unwrap();
+        let cached = cache_dir.path().join("tool.jsonc");
+        assert_eq!(result, Some(cached.clone()));
+        assert_eq!(fs::read_to_string(cached).unwrap(), "{\"a\": 1}");
+
+        let missing = fetch_url_manifest(
+            &base_url,
+            "missing",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(0),
+        )
+        .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn test_fetch_url_manifest_tries_formats_in_order() {
+        let source_dir = tempdir().unwrap();
+        let cache_dir = tempdir().unwrap();
+        fs::write(source_dir.path().join("tool.yaml"), "a: 1").unwrap();
+        let base_url = format!("file://{}", source_dir.path().display());
+        let formats = vec!["jsonc".to_string(), "yaml".to_string()];
+
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats,
+            true,
+            None,
+            &quick_policy(0),
+        )
+        .unwrap();
+        let cached = cache_dir.path().join("tool.yaml");
+        assert_eq!(result, Some(cached.clone()));
+
+        // Without a refresh the cached yaml copy is used even though jsonc comes first
+        fs::remove_file(source_dir.path().join("tool.yaml")).unwrap();
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats,
+            false,
+            None,
+            &quick_policy(0),
+        )
+        .unwrap();
+        assert_eq!(result, Some(cached));
+    }
+
+    #[test]
+    fn test_fetch_url_index_from_file_url() {
+        let source_dir = tempdir().unwrap();
+        let cache_dir = tempdir().unwrap();
+        let base_url = format!("file://{}", source_dir.path().display());
+        let cached = cache_dir.path().join("index.json");
+
+        assert!(
+            fetch_url_index(&base_url, &cached, true, None, &quick_policy(0))
+                .unwrap()
+                .is_none()
+        );
+
+        fs::write(source_dir.path().join("index.json"), r#"["fd", "ripgrep"]"#).unwrap();
+        let tools = fetch_url_index(&base_url, &cached, true, None, &quick_policy(0)).unwrap();
+        assert_eq!(tools, Some(vec!["fd".to_string(), "ripgrep".to_string()]));
+    }
+
+    // Serve `body` only to requests carrying `Authorization: Bearer <token>`, 401 otherwise
+    fn serve_with_token(token: &'static str, body: &'static str) -> String {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0u8; 4096];
+                let len = stream.read(&mut request).unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..len]);
+                let authorized = request
+                    .lines()
+                    .any(|line| line == format!("Authorization: Bearer {}", token));
+
+                let response = if authorized {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        format!("http://{}/manifests", address)
+    }
+
+    #[test]
+    fn test_fetch_url_manifest_sends_token() {
+        let base_url = serve_with_token("secret", "{\"a\": 1}");
+        let cache_dir = tempdir().unwrap();
+
+        let denied = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(0),
+        );
+        assert!(matches!(denied, Err(Error::Network(e)) if e.contains("401")));
+
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            Some("secret"),
+            &quick_policy(0),
+        )
+        .unwrap();
+        let cached = cache_dir.path().join("tool.jsonc");
+        assert_eq!(result, Some(cached.clone()));
+        assert_eq!(fs::read_to_string(cached).unwrap(), "{\"a\": 1}");
+    }
+
+    /// Probe answering from fixed results instead of the network
+    struct MockProbe {
+        git: std::result::Result<bool, &'static str>,
+        http: std::result::Result<u16, &'static str>,
+    }
+
+    impl RemoteProbe for MockProbe {
+        fn git_branch_exists(&self, _url: &str, _branch: Option<&str>) -> Result<bool> {
+            self.git.map_err(|e| Error::Network(e.to_string()))
+        }
+
+        fn http_head(&self, _url: &str, _token: Option<&str>) -> Result<u16> {
+            self.http.map_err(|e| Error::Network(e.to_string()))
+        }
+    }
+
+    fn remote_source(source_type: &str, branch: Option<&str>) -> ManifestSource {
+        ManifestSource {
+            source_type: source_type.to_string(),
+            path: "https://example.com/manifests".to_string(),
+            branch: branch.map(String::from),
+            subdir: None,
+            auto_update: true,
+            auth: None,
+        }
+    }
+
+    #[test]
+    fn test_check_source_reachable() {
+        let probe = MockProbe {
+            git: Ok(true),
+            http: Ok(404),
+        };
+
+        let git = remote_source("git", Some("main"));
+        assert_eq!(check_source(&git, &probe, None), SourceCheck::Reachable);
+        let url = remote_source("url", None);
+        assert_eq!(check_source(&url, &probe, None), SourceCheck::Reachable);
+        let local = remote_source("local", None);
+        assert_eq!(check_source(&local, &probe, None), SourceCheck::NotRemote);
+    }
+
+    #[test]
+    fn test_check_source_unreachable() {
+        let down = MockProbe {
+            git: Err("connection refused"),
+            http: Err("connection refused"),
+        };
+        let git = remote_source("git", Some("main"));
+        assert!(matches!(
+            check_source(&git, &down, None),
+            SourceCheck::Unreachable(e) if e.contains("connection refused")
+        ));
+        let url = remote_source("url", None);
+        assert!(matches!(
+            check_source(&url, &down, None),
+            SourceCheck::Unreachable(e) if e.contains("connection refused")
+        ));
+
+        let answering = MockProbe {
+            git: Ok(false),
+            http: Ok(503),
+        };
+        assert_eq!(
+            check_source(&git, &answering, None),
+            SourceCheck::MissingBranch("main".to_string())
+        );
+        assert_eq!(
+            check_source(&url, &answering, None),
+            SourceCheck::Unreachable("HTTP status 503".to_string())
+        );
+    }
+
+    // Answer each request with the next status from `statuses` (and `body` on 200)
+    fn serve_statuses(statuses: Vec<u16>, body: &'static str) -> String {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for (mut stream, status) in listener.incoming().flatten().zip(statuses) {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let body = if status == 200 { body } else { "" };
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        format!("http://{}/manifests", address)
+    }
+
+    #[test]
+    fn test_fetch_retries_transient_failure() {
+        let base_url = serve_statuses(vec![503, 200], "{\"a\": 1}");
+        let cache_dir = tempdir().unwrap();
+
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(2),
+        )
+        .unwrap();
+        let cached = cache_dir.path().join("tool.jsonc");
+        assert_eq!(result, Some(cached.clone()));
+        assert_eq!(fs::read_to_string(cached).unwrap(), "{\"a\": 1}");
+    }
+
+    #[test]
+    fn test_fetch_keeps_cached_copy_on_404() {
+        let base_url = serve_statuses(vec![404], "");
+        let cache_dir = tempdir().unwrap();
+        let cached = cache_dir.path().join("tool.jsonc");
+        fs::write(&cached, "{}").unwrap();
+
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(0),
+        )
+        .unwrap();
+        assert_eq!(result, Some(cached.clone()));
+        assert_eq!(fs::read_to_string(cached).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_fetch_starter_downloads_fresh_and_fails_when_unreachable() {
+        let base_url = serve_statuses(vec![200], "{\"a\": 1}");
+        let starter = fetch_starter(&format!("{}/starter.jsonc", base_url), &quick_policy(0));
+        assert_eq!(starter.unwrap(), "{\"a\": 1}");
+
+        let unreachable = fetch_starter(
+            &format!("{}/starter.jsonc", UNREACHABLE_URL),
+            &quick_policy(0),
+        );
+        assert!(matches!(unreachable, Err(Error::Network(_))));
+    }
+
+    #[test]
+    fn test_fetch_gives_up_after_retries() {
+        let base_url = serve_statuses(vec![503, 503], "");
+        let cache_dir = tempdir().unwrap();
+
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(1),
+        );
+        assert!(matches!(
+            result,
+            Err(Error::Network(e)) if e.contains("HTTP status 503") && e.contains("gave up after 2 attempts")
+        ));
+    }
+
+    #[test]
+    fn test_fetch_times_out_on_stalled_server() {
+        // Accepts the connection but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/manifests", listener.local_addr().unwrap());
+        let cache_dir = tempdir().unwrap();
+
+        let started = std::time::Instant::now();
+        let result = fetch_url_manifest(
+            &base_url,
+            "tool",
+            cache_dir.path(),
+            &formats(),
+            true,
+            None,
+            &quick_policy(0),
+        );
+        assert!(matches!(result, Err(Error::Network(_))));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        drop(listener);
+    }
+
+    #[test]
+    fn test_only_connection_failures_are_retried() {
+        assert!(curl_failure_is_transient(Some(7)));
+        assert!(curl_failure_is_transient(Some(28)));
+        assert!(!curl_failure_is_transient(Some(3)));
+        assert!(!curl_failure_is_transient(Some(60)));
+        assert!(!curl_failure_is_transient(None));
+
+        assert!(git_failure_is_transient(
+            "fatal: unable to access 'https://example.com/repo.git/': Could not resolve host: example.com"
+        ));
+        assert!(git_failure_is_transient(
+            "ssh: connect to host example.com port 22: Connection timed out"
+        ));
+        assert!(!git_failure_is_transient(
+            "remote: Repository not found.\nfatal: repository 'https://example.com/repo.git/' not found"
+        ));
+        assert!(!git_failure_is_transient(
+            "git@example.com: Permission denied (publickey).\nfatal: Could not read from remote repository."
+        ));
+        assert!(!git_failure_is_transient(
+            "fatal: Not possible to fast-forward, aborting."
+        ));
+    }
+}
@@ -1,7 +1,8 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
 use std::fs;
-use tempfile::{tempdir, TempDir};
+use std::path::{Path, PathBuf};
+use tempfile::{TempDir, tempdir};
 
 fn create_isolated_command() -> Command {
     let temp_dir = tempdir().unwrap();
@@ -9,6 +10,7 @@ fn create_isolated_command() -> Command {
 
     // Use a temporary directory for tests to avoid interference
     cmd.env("XDG_CONFIG_HOME", temp_dir.path())
+        .env("XDG_CACHE_HOME", temp_dir.path().join(".cache"))
         .env("HOME", temp_dir.path());
 
     // Keep the temp_dir alive by storing it in an environment variable
@@ -24,7 +26,7 @@ fn create_isolated_command() -> Command {
 // Helper for tests that need to share state across multiple commands
 fn with_shared_test_env<F>(test_fn: F)
 where
-    F: FnOnce(&TempDir) -> (),
+    F: FnOnce(&TempDir),
 {
     let temp_dir = tempdir().unwrap();
     test_fn(&temp_dir);
@@ -34,11 +36,67 @@ fn create_command_with_env(temp_dir: &TempDir) -> Command {
     let mut cmd = Command::cargo_bin("tool-meister").unwrap();
 
     cmd.env("XDG_CONFIG_HOME", temp_dir.path())
+        .env("XDG_CACHE_HOME", temp_dir.path().join(".cache"))
         .env("HOME", temp_dir.path());
 
     cmd
 }
 
+// Register a local manifest directory as a source in the shared test env
+fn add_local_source(config_dir: &TempDir, manifest_dir: &Path) {
+    let mut add_cmd = create_command_with_env(config_dir);
+    add_cmd
+        .arg("manifests")
+        .arg("add-source")
+        .arg("--source-type")
+        .arg("local")
+        .arg(manifest_dir.to_str().unwrap());
+    add_cmd.assert().success();
+}
+
+// Write the app configuration file of the shared test env, returning its path
+fn write_app_config(config_dir: &TempDir, content: &str) -> PathBuf {
+    let mut print_cmd = create_command_with_env(config_dir);
+    let output = print_cmd.arg("--print-config-path").assert().success();
+    let config_path = PathBuf::from(
+        String::from_utf8(output.get_output().stdout.clone())
+            .unwrap()
+            .trim(),
+    );
+    fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    fs::write(&config_path, content).unwrap();
+    config_path
+}
+
+// Serve `files` (request path -> body) over HTTP on localhost, answering 404 otherwise
+fn serve_http(files: Vec<(&'static str, &'static str)>) -> String {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0u8; 4096];
+            let len = stream.read(&mut request).unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..len]);
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+            let response = match files.iter().find(|(file, _)| *file == path) {
+                Some((_, body)) => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                ),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    format!("http://{}", address)
+}
+
 #[test]
 fn test_manifests_help() {
     let mut cmd = create_isolated_command();
@@ -127,7 +185,9 @@ fn test_manifests_add_source_valid_local_directory() {
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("✅ Added manifest source: local"))
+        .stdout(predicate::str::contains(
+            "[OK] Added manifest source: local",
+        ))
         .stdout(predicate::str::contains(manifest_dir.to_str().unwrap()));
 }
 
@@ -159,12 +219,13 @@ fn test_manifests_add_source_valid_git_url() {
     cmd.assert()
         .success()
         .stdout(predicate::str::contains(
-            "✅ Added manifest source: git https://github.com/example/manifests.git",
+            "[OK] Added manifest source: git https://github.com/example/manifests.git",
         ))
         .stdout(predicate::str::contains("(branch: main)"));
 }
 
 #[test]
+#[allow(non_snake_case)]
 fn test_manifests_add_source__invalid_url() {
     let mut cmd = create_isolated_command();
     cmd.arg("manifests")
@@ -191,7 +252,7 @@ fn test_manifests_add_source_valid_url() {
     cmd.assert()
         .success()
         .stdout(predicate::str::contains(
-            "✅ Added manifest source: url https://example.com/manifests",
+            "[OK] Added manifest source: url https://example.com/manifests",
         ))
         .stdout(predicate::str::contains("without auto-update"));
 }
@@ -292,7 +353,7 @@ fn test_config_reset() {
     cmd.arg("config").arg("--reset");
 
     cmd.assert().success().stdout(predicate::str::contains(
-        "✅ App configuration reset to defaults",
+        "[OK] App configuration reset to defaults",
     ));
 }
 
@@ -328,7 +389,9 @@ fn test_relative_path_resolution() {
 
     // The output should show the absolute path, not the relative one
     output
-        .stdout(predicate::str::contains("✅ Added manifest source: local"))
+        .stdout(predicate::str::contains(
+            "[OK] Added manifest source: local",
+        ))
         .stdout(predicate::str::contains(manifest_dir.to_str().unwrap()));
 
     // Restore original directory
@@ -420,4 +483,3322 @@ fn test_run_command_failure_captures_output() {
             .stderr(predicate::str::contains("-- stderr --"))
             .stderr(predicate::str::contains("No such file or directory"));
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_run_quiet_spawn_hides_banner() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let marker = config_dir.path().join("spawned.marker");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "spawn-tool",
+    "url": "https://github.com/example/spawn.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [
+      {{
+        "seq-id": 1,
+        "description": "Touch a marker file",
+        "command": "touch {}",
+        "spawn": true
+      }}
+    ]
+  }}
+}}"#,
+            marker.display()
+        );
+        fs::write(manifest_dir.join("spawn-tool.jsonc"), test_manifest).unwrap();
+
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .arg("manifests")
+            .arg("add-source")
+            .arg("--source-type")
+            .arg("local")
+            .arg(manifest_dir.to_str().unwrap());
+        add_cmd.assert().success();
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("spawn-tool");
+        install_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("run").arg("--quiet-spawn").arg("spawn-tool");
+
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Spawned").not());
+
+        // The detached process may finish after we return, so poll briefly
+        let mut spawned = false;
+        for _ in 0..50 {
+            if marker.exists() {
+                spawned = true;
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        assert!(spawned, "spawned process did not run");
+    });
+}
+
+#[test]
+fn test_install_dir_overrides_repo_directory() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let marker = config_dir.path().join("cwd.txt");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "dir-tool",
+    "url": "https://github.com/example/dir.git",
+    "default_branch": {{ "name": "main" }},
+    "install_dir": "[[repo.name]]-custom"
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [
+      {{
+        "seq-id": 1,
+        "description": "Record working directory",
+        "command": "pwd > {}"
+      }}
+    ]
+  }}
+}}"#,
+            marker.display()
+        );
+        fs::write(manifest_dir.join("dir-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("dir-tool");
+        install_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("run").arg("dir-tool");
+        cmd.assert().success();
+
+        let cwd = fs::read_to_string(&marker).unwrap();
+        assert!(cwd.trim_end().ends_with("dir-tool-custom"));
+    });
+}
+
+#[test]
+fn test_install_dir_rejects_traversal() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "bad-tool",
+    "url": "https://github.com/example/bad.git",
+    "default_branch": { "name": "main" },
+    "install_dir": "../escape"
+  },
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        fs::write(manifest_dir.join("bad-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("install").arg("bad-tool");
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("install_dir"));
+    });
+}
+
+#[test]
+fn test_version_command_prints_metadata() {
+    with_shared_test_env(|config_dir| {
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("version");
+
+        let config_path = config_dir.path().join("tool-meister").join("config.toml");
+
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")))
+            .stdout(predicate::str::contains(config_path.to_str().unwrap()))
+            .stdout(predicate::str::contains("config_file_name"));
+    });
+}
+
+#[test]
+fn test_corrupt_config_falls_back_to_defaults() {
+    with_shared_test_env(|config_dir| {
+        let app_dir = config_dir.path().join("tool-meister");
+        fs::create_dir_all(&app_dir).unwrap();
+        fs::write(app_dir.join("config.toml"), "this is = = not toml [").unwrap();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("manifests").arg("list");
+
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Configured manifest sources:"))
+            .stderr(predicate::str::contains("continuing with defaults"));
+
+        let backup = fs::read_to_string(app_dir.join("config.toml.bak")).unwrap();
+        assert_eq!(backup, "this is = = not toml [");
+        assert!(app_dir.join("config.toml").exists());
+    });
+}
+
+#[test]
+fn test_run_args_file_prepends_arguments() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "echo-tool",
+    "url": "https://github.com/example/echo.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Echo arguments", "command": "echo" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("echo-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let args_file = config_dir.path().join("args.txt");
+        fs::write(&args_file, "# saved arguments\n--from-file one\n\ntwo\n").unwrap();
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("echo-tool");
+        install_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("run")
+            .arg("--args-file")
+            .arg(&args_file)
+            .arg("echo-tool")
+            .arg("inline");
+
+        cmd.assert().success().stdout(predicate::str::contains(
+            "Executing: echo --from-file one two inline",
+        ));
+    });
+}
+
+#[test]
+fn test_phase_steps_show_progress() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "steps-tool",
+    "url": "https://github.com/example/steps.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "first", "command": "true" },
+      { "seq-id": 2, "description": "second", "command": "true" },
+      { "seq-id": 3, "description": "third", "command": "true" }
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("steps-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("install").arg("steps-tool");
+
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let stdout = String::from_utf8(output).unwrap();
+        let first = stdout.find("[1/3] Step 1: first").unwrap();
+        let second = stdout.find("[2/3] Step 2: second").unwrap();
+        let third = stdout.find("[3/3] Step 3: third").unwrap();
+        assert!(first < second && second < third);
+    });
+}
+
+#[test]
+fn test_print_config_path_exits_early() {
+    with_shared_test_env(|config_dir| {
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--print-config-path");
+
+        let expected = config_dir.path().join("tool-meister").join("config.toml");
+
+        cmd.assert()
+            .success()
+            .stdout(format!("{}\n", expected.display()));
+
+        // Printing the path must not create the config file
+        assert!(!expected.exists());
+    });
+}
+
+#[test]
+fn test_config_reset_empty_has_no_sources() {
+    with_shared_test_env(|config_dir| {
+        let mut reset_cmd = create_command_with_env(config_dir);
+        reset_cmd.arg("config").arg("--reset").arg("--empty");
+        reset_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("without manifest sources"));
+
+        let config =
+            fs::read_to_string(config_dir.path().join("tool-meister/config.toml")).unwrap();
+        assert!(config.contains("manifest_sources = []"));
+
+        let mut list_cmd = create_command_with_env(config_dir);
+        list_cmd.arg("manifests").arg("list");
+        list_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No manifest sources configured."));
+    });
+}
+
+#[test]
+fn test_run_with_deps_checks_dependencies() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let marker = config_dir.path().join("dep-checked");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "deps-tool",
+    "url": "https://github.com/example/deps.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [
+    {{
+      "name": "marker",
+      "version": "1.0",
+      "url": "https://example.com",
+      "check": "touch {}"
+    }}
+  ],
+  "actions": {{
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{{ "seq-id": 1, "description": "noop", "command": "true" }}]
+  }}
+}}"#,
+            marker.display()
+        );
+        fs::write(manifest_dir.join("deps-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("deps-tool");
+        install_cmd.assert().success();
+        // Install always sets up dependencies; plain runs must not check them again
+        assert!(marker.exists());
+        fs::remove_file(&marker).unwrap();
+
+        let mut run_cmd = create_command_with_env(config_dir);
+        run_cmd.arg("run").arg("deps-tool");
+        run_cmd.assert().success();
+        assert!(!marker.exists());
+
+        let mut deps_cmd = create_command_with_env(config_dir);
+        deps_cmd.arg("run").arg("--with-deps").arg("deps-tool");
+        deps_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Dependency present: marker"));
+        assert!(marker.exists());
+    });
+}
+
+#[test]
+fn test_install_summary_line() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "summary-tool",
+    "url": "https://github.com/example/summary.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "first", "command": "true" },
+      { "seq-id": 2, "description": "second", "command": "true" }
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("summary-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--summary").arg("install").arg("summary-tool");
+
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let stdout = String::from_utf8(output).unwrap();
+        let last_line = stdout.lines().last().unwrap();
+        assert!(last_line.starts_with(
+            "TOOL_MEISTER_RESULT tool=summary-tool action=install status=ok steps=2 duration_ms="
+        ));
+    });
+}
+
+#[test]
+fn test_action_shell_override_uses_bash() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let marker = config_dir.path().join("bash-version");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "bash-tool",
+    "url": "https://github.com/example/bash.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [
+      {{
+        "seq-id": 1,
+        "description": "bashism",
+        "command": "[[ -n \"$BASH_VERSION\" ]] && echo \"$BASH_VERSION\" > {}",
+        "shell": "bash"
+      }}
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }}
+}}"#,
+            marker.display()
+        );
+        fs::write(manifest_dir.join("bash-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("install").arg("bash-tool");
+        cmd.assert().success();
+
+        let version = fs::read_to_string(&marker).unwrap();
+        assert!(!version.trim().is_empty());
+    });
+}
+
+#[test]
+fn test_action_shell_override_missing_shell() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "noshell-tool",
+    "url": "https://github.com/example/noshell.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "x", "command": "true", "shell": "no-such-shell-xyz" }
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("noshell-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("install").arg("noshell-tool");
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("Shell not found"));
+    });
+}
+
+#[test]
+fn test_all_stops_after_failed_phase() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let marker = config_dir.path().join("ran");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "all-tool",
+    "url": "https://github.com/example/all.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [],
+    "update": [],
+    "build": [{{ "seq-id": 1, "description": "broken build", "command": "false" }}],
+    "run": [{{ "seq-id": 1, "description": "mark run", "command": "touch {}" }}]
+  }}
+}}"#,
+            marker.display()
+        );
+        fs::write(manifest_dir.join("all-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--summary").arg("all").arg("all-tool");
+        cmd.assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "TOOL_MEISTER_RESULT tool=all-tool action=all status=error steps=2",
+            ))
+            .stdout(predicate::str::contains("[OK] installation"))
+            .stdout(predicate::str::contains("[FAIL] build"))
+            .stdout(predicate::str::contains("run (skipped)"));
+        assert!(!marker.exists());
+
+        let mut keep_going_cmd = create_command_with_env(config_dir);
+        keep_going_cmd
+            .arg("all")
+            .arg("--keep-going")
+            .arg("all-tool");
+        keep_going_cmd
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("[OK] run"));
+        assert!(marker.exists());
+    });
+}
+
+#[test]
+fn test_manifests_diff_shows_changes() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::write(
+            manifest_dir.join("diff-tool.jsonc"),
+            "{\n  \"name\": \"old-value\"\n}\n",
+        )
+        .unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut first = create_command_with_env(config_dir);
+        first
+            .arg("manifests")
+            .arg("diff")
+            .arg(manifest_dir.to_str().unwrap());
+        first
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("+ new: diff-tool"));
+
+        fs::write(
+            manifest_dir.join("diff-tool.jsonc"),
+            "{\n  \"name\": \"new-value\"\n}\n",
+        )
+        .unwrap();
+        fs::write(manifest_dir.join("other-tool.jsonc"), "{}\n").unwrap();
+
+        let mut second = create_command_with_env(config_dir);
+        second
+            .arg("manifests")
+            .arg("diff")
+            .arg(manifest_dir.to_str().unwrap());
+        second
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("~ changed: diff-tool"))
+            .stdout(predicate::str::contains("-  \"name\": \"old-value\""))
+            .stdout(predicate::str::contains("+  \"name\": \"new-value\""))
+            .stdout(predicate::str::contains("+ new: other-tool"));
+
+        let mut third = create_command_with_env(config_dir);
+        third
+            .arg("manifests")
+            .arg("diff")
+            .arg(manifest_dir.to_str().unwrap());
+        third
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No changes since the last diff"));
+    });
+}
+
+#[test]
+fn test_file_url_source_resolves_manifest() {
+    with_shared_test_env(|config_dir| {
+        let mirror_dir = config_dir.path().join("mirror");
+        fs::create_dir_all(&mirror_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "mirror-tool",
+    "url": "https://github.com/example/mirror.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "from mirror", "command": "true" }],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(mirror_dir.join("mirror-tool.jsonc"), test_manifest).unwrap();
+
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .arg("manifests")
+            .arg("add-source")
+            .arg("--source-type")
+            .arg("url")
+            .arg(format!("file://{}", mirror_dir.display()));
+        add_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("install").arg("mirror-tool");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Step 1: from mirror"));
+    });
+}
+
+#[test]
+fn test_run_filter_forwards_matching_args() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "filter-tool",
+    "url": "https://github.com/example/filter.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Echo arguments", "command": "echo" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("filter-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("filter-tool");
+        install_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("run")
+            .arg("--filter")
+            .arg("^--")
+            .arg("filter-tool")
+            .arg("--verbose")
+            .arg("--debug")
+            .arg("info");
+
+        cmd.assert().success().stdout(predicate::str::contains(
+            "Executing: echo --verbose --debug\n",
+        ));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn test_direct_command_expands_home() {
+    use std::os::unix::fs::PermissionsExt;
+
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        // HOME points at the test dir, so ~/bin/mark resolves to this script
+        let bin_dir = config_dir.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        let script = bin_dir.join("mark");
+        fs::write(&script, "#!/bin/sh\necho x >> \"$HOME/marks\"\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "home-tool",
+    "url": "https://github.com/example/home.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "tilde", "command": "~/bin/mark" },
+      { "seq-id": 2, "description": "env var", "command": "$HOME/bin/mark" }
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("home-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("install").arg("home-tool");
+        cmd.assert().success();
+
+        let marks = fs::read_to_string(config_dir.path().join("marks")).unwrap();
+        assert_eq!(marks.lines().count(), 2);
+    });
+}
+
+#[test]
+fn test_manifests_info_all_sources_lists_git_cache() {
+    with_shared_test_env(|config_dir| {
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .arg("manifests")
+            .arg("add-source")
+            .arg("--source-type")
+            .arg("git")
+            .arg("https://github.com/example/cached.git");
+        add_cmd.assert().success();
+
+        let cache_dir = config_dir
+            .path()
+            .join(".cache")
+            .join("tool-meister")
+            .join("https___github_com_example_cached_git");
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join("cached-tool.jsonc"), "{}").unwrap();
+
+        let mut plain_cmd = create_command_with_env(config_dir);
+        plain_cmd.arg("manifests").arg("info");
+        plain_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("cached-tool").not());
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("manifests").arg("info").arg("--all-sources");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Cached manifests"))
+            .stdout(predicate::str::contains("- cached-tool"));
+    });
+}
+
+#[test]
+fn test_tools_dir_override() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let scratch = config_dir.path().join("scratch");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "scratch-tool",
+    "url": "https://github.com/example/scratch.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "write file", "command": "touch installed.txt" }],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("scratch-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--tools-dir")
+            .arg(&scratch)
+            .arg("install")
+            .arg("scratch-tool");
+        cmd.assert().success();
+
+        assert!(scratch.join("scratch-tool").is_dir());
+        assert!(scratch.join("installed.txt").exists());
+    });
+}
+
+#[test]
+fn test_plain_output_has_no_emoji() {
+    with_shared_test_env(|config_dir| {
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--plain").arg("config").arg("--reset");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let stdout = String::from_utf8(output).unwrap();
+        assert!(stdout.contains("[OK] App configuration reset to defaults"));
+        assert!(stdout.is_ascii(), "unexpected non-ASCII output: {}", stdout);
+    });
+}
+
+#[test]
+fn test_action_runs_script_file() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        let tools_dir = config_dir.path().join("script-tools");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::create_dir_all(&tools_dir).unwrap();
+        let marker = config_dir.path().join("script-ran");
+
+        // The script ships next to the manifest, not in the tools directory
+        fs::write(
+            manifest_dir.join("setup.sh"),
+            format!("echo \"from script\" > {}\n", marker.display()),
+        )
+        .unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "script-tool",
+    "url": "https://github.com/example/script.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "run setup script", "script": "setup.sh" }
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("script-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--tools-dir")
+            .arg(&tools_dir)
+            .arg("install")
+            .arg("script-tool");
+        cmd.assert().success();
+
+        assert_eq!(fs::read_to_string(&marker).unwrap().trim(), "from script");
+    });
+}
+
+#[test]
+fn test_jobs_overrides_max_concurrency() {
+    with_shared_test_env(|config_dir| {
+        let mut show_cmd = create_command_with_env(config_dir);
+        show_cmd.arg("config").arg("--show");
+        show_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("max_concurrency = 4"));
+
+        let mut info_cmd = create_command_with_env(config_dir);
+        info_cmd.arg("-j").arg("1").arg("manifests").arg("info");
+        info_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Manifest source information:"));
+
+        let mut zero_cmd = create_command_with_env(config_dir);
+        zero_cmd.arg("--jobs").arg("0").arg("manifests").arg("info");
+        zero_cmd.assert().failure();
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn test_world_writable_manifest_dir_warns_or_refuses() {
+    use std::os::unix::fs::PermissionsExt;
+
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("open-manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::set_permissions(&manifest_dir, fs::Permissions::from_mode(0o777)).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "open-tool",
+    "url": "https://github.com/example/open.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        fs::write(manifest_dir.join("open-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut warn_cmd = create_command_with_env(config_dir);
+        warn_cmd.arg("build").arg("open-tool");
+        warn_cmd
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("is world-writable"));
+
+        let mut strict_cmd = create_command_with_env(config_dir);
+        strict_cmd
+            .arg("--strict-security")
+            .arg("build")
+            .arg("open-tool");
+        strict_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Refusing to load manifest"));
+    });
+}
+
+#[test]
+fn test_run_explain_prints_spawn_reasoning() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "explain-tool",
+    "url": "https://github.com/example/explain.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Launch", "command": "echo", "spawn": true }]
+  }
+}"#;
+        fs::write(manifest_dir.join("explain-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("explain-tool");
+        install_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("run")
+            .arg("--explain")
+            .arg("explain-tool")
+            .arg("notes.txt")
+            .arg("--version");
+
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "[explain] Step 1: waiting: info argument detected (--version)",
+            ))
+            .stdout(predicate::str::contains("Completed: Launch"));
+    });
+}
+
+#[test]
+fn test_manifests_info_lists_url_index() {
+    with_shared_test_env(|config_dir| {
+        let base_url = serve_http(vec![("/manifests/index.json", r#"["fd", "ripgrep"]"#)]);
+
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .arg("manifests")
+            .arg("add-source")
+            .arg("--source-type")
+            .arg("url")
+            .arg(format!("{}/manifests", base_url));
+        add_cmd.assert().success();
+
+        let mut info_cmd = create_command_with_env(config_dir);
+        info_cmd
+            .arg("manifests")
+            .arg("info")
+            .arg("--source")
+            .arg("url");
+        info_cmd.assert().success().stdout(predicate::str::contains(
+            "Available manifests (from index.json):\n    - fd\n    - ripgrep",
+        ));
+    });
+}
+
+#[test]
+fn test_manifests_set_branch_updates_git_source() {
+    with_shared_test_env(|config_dir| {
+        let mut reset_cmd = create_command_with_env(config_dir);
+        reset_cmd.arg("config").arg("--reset").arg("--empty");
+        reset_cmd.assert().success();
+
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .arg("manifests")
+            .arg("add-source")
+            .arg("--source-type")
+            .arg("git")
+            .arg("--branch")
+            .arg("main")
+            .arg("https://github.com/example/manifests.git");
+        add_cmd.assert().success();
+        add_local_source(config_dir, config_dir.path());
+
+        let mut set_cmd = create_command_with_env(config_dir);
+        set_cmd
+            .arg("manifests")
+            .arg("set-branch")
+            .arg("1")
+            .arg("develop");
+        set_cmd.assert().success().stdout(predicate::str::contains(
+            "Set branch of manifest source 1 to develop",
+        ));
+
+        let mut list_cmd = create_command_with_env(config_dir);
+        list_cmd.arg("manifests").arg("list");
+        list_cmd.assert().success().stdout(predicate::str::contains(
+            "1: git https://github.com/example/manifests.git [auto-update] (branch: develop)",
+        ));
+
+        let mut local_cmd = create_command_with_env(config_dir);
+        local_cmd
+            .arg("manifests")
+            .arg("set-branch")
+            .arg("2")
+            .arg("develop");
+        local_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Only git sources have a branch"));
+    });
+}
+
+#[test]
+fn test_run_no_interpolate_executes_literal_command() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "literal-tool",
+    "url": "https://github.com/example/literal.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Echo name", "command": "echo '[[repo.name]]'" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("literal-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("literal-tool");
+        install_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("run")
+            .arg("--no-interpolate")
+            .arg("literal-tool")
+            .arg("extra");
+        cmd.assert().success().stdout(predicate::str::contains(
+            "Executing: echo '[[repo.name]]' extra\n",
+        ));
+
+        let mut interpolated_cmd = create_command_with_env(config_dir);
+        interpolated_cmd.arg("run").arg("literal-tool");
+        interpolated_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Executing: echo 'literal-tool'\n"));
+    });
+}
+
+#[test]
+fn test_git_source_requires_git_on_path() {
+    with_shared_test_env(|config_dir| {
+        let mut reset_cmd = create_command_with_env(config_dir);
+        reset_cmd.arg("config").arg("--reset").arg("--empty");
+        reset_cmd.assert().success();
+
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .arg("manifests")
+            .arg("add-source")
+            .arg("--source-type")
+            .arg("git")
+            .arg("https://github.com/example/manifests.git");
+        add_cmd.assert().success();
+
+        // An empty PATH directory guarantees git can't be found
+        let empty_path = config_dir.path().join("empty-bin");
+        fs::create_dir_all(&empty_path).unwrap();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.env("PATH", &empty_path).args(["manifests", "update"]);
+        cmd.assert().failure().stdout(predicate::str::contains(
+            "git is required to fetch manifest source https://github.com/example/manifests.git",
+        ));
+
+        // Looking a tool up skips the uncloned git source instead of failing on it
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::write(
+            manifest_dir.join("some-tool.jsonc"),
+            r#"{
+  "repo": { "name": "some-tool", "url": "https://github.com/example/some.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#,
+        )
+        .unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut show_cmd = create_command_with_env(config_dir);
+        show_cmd
+            .env("PATH", &empty_path)
+            .args(["manifests", "show", "some-tool"]);
+        show_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("some-tool"));
+    });
+}
+
+#[test]
+fn test_history_since_filters_old_entries() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "recent-tool",
+    "url": "https://github.com/example/recent.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        fs::write(manifest_dir.join("recent-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let history_path = config_dir.path().join("tool-meister/history.jsonl");
+        fs::write(
+            &history_path,
+            r#"{"tool":"old-tool","action":"build","status":"ok","timestamp":1000,"duration_ms":3}
+"#,
+        )
+        .unwrap();
+
+        let mut build_cmd = create_command_with_env(config_dir);
+        build_cmd.arg("build").arg("recent-tool");
+        build_cmd.assert().success();
+
+        let mut all_cmd = create_command_with_env(config_dir);
+        all_cmd.arg("history");
+        all_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("old-tool"))
+            .stdout(predicate::str::contains("recent-tool  build    ok"));
+
+        let mut since_cmd = create_command_with_env(config_dir);
+        since_cmd.arg("history").arg("--since").arg("24h");
+        since_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("recent-tool"))
+            .stdout(predicate::str::contains("old-tool").not());
+
+        let mut tool_cmd = create_command_with_env(config_dir);
+        tool_cmd
+            .arg("history")
+            .arg("old-tool")
+            .arg("--since")
+            .arg("7d");
+        tool_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No history entries found."));
+
+        let mut invalid_cmd = create_command_with_env(config_dir);
+        invalid_cmd.arg("history").arg("--since").arg("soon");
+        invalid_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid duration 'soon'"));
+    });
+}
+
+#[test]
+fn test_run_watch_reruns_on_change() {
+    use std::time::{Duration, Instant};
+
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        let watched_dir = config_dir.path().join("watched");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::create_dir_all(&watched_dir).unwrap();
+        let counter = config_dir.path().join("runs");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "watch-tool",
+    "url": "https://github.com/example/watch.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{{ "seq-id": 1, "description": "Count run", "command": "echo run >> {}; sleep 30" }}]
+  }}
+}}"#,
+            counter.display()
+        );
+        fs::write(manifest_dir.join("watch-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("watch-tool");
+        install_cmd.assert().success();
+
+        let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("tool-meister"))
+            .env("XDG_CONFIG_HOME", config_dir.path())
+            .env("XDG_CACHE_HOME", config_dir.path().join(".cache"))
+            .env("HOME", config_dir.path())
+            .arg("run")
+            .arg("--watch")
+            .arg(&watched_dir)
+            .arg("watch-tool")
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let runs = || {
+            fs::read_to_string(&counter)
+                .map(|content| content.lines().count())
+                .unwrap_or(0)
+        };
+        let wait_for_runs = |expected: usize| {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while runs() < expected && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            runs() >= expected
+        };
+
+        // The first run is still sleeping when the change arrives, so it must be cancelled
+        let first_run = wait_for_runs(1);
+        if first_run {
+            fs::write(watched_dir.join("source.txt"), "changed").unwrap();
+        }
+        let second_run = first_run && wait_for_runs(2);
+
+        #[cfg(unix)]
+        let stopped = {
+            std::process::Command::new("kill")
+                .args(["-TERM", &child.id().to_string()])
+                .status()
+                .unwrap();
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                if let Some(status) = child.try_wait().unwrap() {
+                    break Some(status);
+                }
+                if Instant::now() > deadline {
+                    break None;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        };
+        #[cfg(not(unix))]
+        let stopped: Option<std::process::ExitStatus> = None;
+        if stopped.is_none() {
+            child.kill().unwrap();
+            child.wait().unwrap();
+        }
+        assert!(first_run, "the initial run never happened");
+        assert!(
+            second_run,
+            "changing a watched file did not trigger a re-run"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            stopped.and_then(|status| status.code()),
+            Some(143),
+            "SIGTERM did not stop the watch"
+        );
+    });
+}
+
+#[test]
+fn test_config_reset_keep_sources_preserves_sources() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("kept-manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let config_path = config_dir.path().join("tool-meister/config.toml");
+        let config = fs::read_to_string(&config_path).unwrap();
+        assert!(config.contains("max_concurrency = 4"));
+        fs::write(
+            &config_path,
+            config.replace("max_concurrency = 4", "max_concurrency = 9"),
+        )
+        .unwrap();
+
+        let mut reset_cmd = create_command_with_env(config_dir);
+        reset_cmd.arg("config").arg("--reset").arg("--keep-sources");
+        reset_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("keeping manifest sources"));
+
+        let config = fs::read_to_string(&config_path).unwrap();
+        assert!(config.contains("max_concurrency = 4"));
+        assert!(config.contains("kept-manifests"));
+    });
+}
+
+#[test]
+fn test_manifests_check_reports_reachable_and_unreachable_sources() {
+    with_shared_test_env(|config_dir| {
+        let mut reset_cmd = create_command_with_env(config_dir);
+        reset_cmd.arg("config").arg("--reset").arg("--empty");
+        reset_cmd.assert().success();
+
+        let base_url = format!("{}/manifests", serve_http(vec![]));
+        for url in [base_url.as_str(), "http://127.0.0.1:1/manifests"] {
+            let mut add_cmd = create_command_with_env(config_dir);
+            add_cmd
+                .arg("manifests")
+                .arg("add-source")
+                .arg("--source-type")
+                .arg("url")
+                .arg(url);
+            add_cmd.assert().success();
+        }
+
+        let mut check_cmd = create_command_with_env(config_dir);
+        check_cmd.arg("manifests").arg("check").arg("1");
+        check_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(format!(
+                "[OK] reachable: url {}",
+                base_url
+            )));
+
+        let mut check_all_cmd = create_command_with_env(config_dir);
+        check_all_cmd.arg("manifests").arg("check");
+        check_all_cmd
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(format!(
+                "[OK] reachable: url {}",
+                base_url
+            )))
+            .stdout(predicate::str::contains(
+                "[FAIL] unreachable: url http://127.0.0.1:1/manifests",
+            ))
+            .stderr(predicate::str::contains(
+                "1 manifest source(s) failed the check",
+            ));
+    });
+}
+
+#[test]
+fn test_install_with_only_dependencies_runs_dependency_setup() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let installed = config_dir.path().join("dep-installed");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "deps-only-tool",
+    "url": "https://github.com/example/deps-only.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [
+    {{
+      "name": "helper",
+      "version": "1.0",
+      "url": "https://example.com",
+      "check": "false",
+      "install": "touch {}"
+    }}
+  ],
+  "actions": {{ "installation": [], "update": [], "build": [], "run": [] }}
+}}"#,
+            installed.display()
+        );
+        fs::write(manifest_dir.join("deps-only-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("deps-only-tool");
+        install_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Installed dependency: helper"))
+            .stdout(predicate::str::contains(
+                "installation consisted only of dependency setup",
+            ));
+        assert!(installed.exists());
+    });
+}
+
+#[test]
+fn test_output_dir_writes_step_logs() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "logged-tool",
+    "url": "https://github.com/example/logged.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [
+      { "seq-id": 1, "description": "stdout", "command": "echo first step" },
+      { "seq-id": 2, "description": "stderr", "command": "echo second step >&2" }
+    ],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("logged-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.arg("install").arg("logged-tool");
+        install_cmd.assert().success();
+
+        let output_dir = config_dir.path().join("step-logs");
+        let mut build_cmd = create_command_with_env(config_dir);
+        build_cmd
+            .arg("--output-dir")
+            .arg(&output_dir)
+            .arg("build")
+            .arg("logged-tool");
+        build_cmd.assert().success();
+
+        let phase_dir = output_dir.join("logged-tool").join("build");
+        assert_eq!(
+            fs::read_to_string(phase_dir.join("1.log")).unwrap(),
+            "first step\n"
+        );
+        assert_eq!(
+            fs::read_to_string(phase_dir.join("2.log")).unwrap(),
+            "second step\n"
+        );
+    });
+}
+
+#[test]
+fn test_install_backup_preserves_previous_install() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("backup-tools");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "backup-tool",
+    "url": "https://github.com/example/backup.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        fs::write(manifest_dir.join("backup-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let install = |extra: &[&str]| {
+            let mut cmd = create_command_with_env(config_dir);
+            cmd.arg("--tools-dir")
+                .arg(&tools_dir)
+                .arg("install")
+                .arg("backup-tool")
+                .args(extra);
+            cmd.assert().success()
+        };
+
+        install(&[]);
+        fs::write(tools_dir.join("backup-tool/local-change.txt"), "keep me").unwrap();
+
+        install(&["--backup"]).stdout(predicate::str::contains("[OK] Moved existing install to"));
+
+        let backups: Vec<_> = fs::read_dir(&tools_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .starts_with("backup-tool.bak-")
+            })
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            fs::read_to_string(backups[0].join("local-change.txt")).unwrap(),
+            "keep me"
+        );
+        assert!(tools_dir.join("backup-tool").is_dir());
+        assert!(!tools_dir.join("backup-tool/local-change.txt").exists());
+    });
+}
+
+#[test]
+fn test_trace_commands_prints_cwd_and_shell() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("trace-tools");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "trace-tool",
+    "url": "https://github.com/example/trace.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [{ "seq-id": 4, "description": "greet", "command": "echo hello", "shell": "bash" }],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("trace-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .arg("install")
+            .arg("trace-tool");
+        install_cmd.assert().success();
+
+        let mut build_cmd = create_command_with_env(config_dir);
+        build_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .arg("--trace-commands")
+            .arg("build")
+            .arg("trace-tool");
+        build_cmd
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(format!(
+                "[trace] step 4 start: cwd={} shell=\"bash\"",
+                tools_dir.join("trace-tool").display()
+            )))
+            .stderr(predicate::str::contains(
+                r#"argv=["bash", "-c", "echo hello"]"#,
+            ))
+            .stderr(predicate::str::contains("[trace] step 4 done: ok in"));
+    });
+}
+
+#[test]
+fn test_add_source_clone_now_warns_on_clone_failure() {
+    with_shared_test_env(|config_dir| {
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("manifests")
+            .arg("add-source")
+            .arg("--source-type")
+            .arg("git")
+            .arg("--clone-now")
+            // Nothing listens on port 1, so the clone fails immediately
+            .arg("http://127.0.0.1:1/manifests.git");
+
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("[OK] Added manifest source: git"))
+            .stderr(predicate::str::contains("[WARN] Initial clone of"));
+    });
+}
+
+#[test]
+fn test_env_file_is_visible_to_run_action() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("env-tools");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "env-tool",
+    "url": "https://github.com/example/env.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "env_file": ".env",
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "print greeting", "command": "echo \"$GREETING\" > seen.txt" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("env-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .arg("install")
+            .arg("env-tool");
+        install_cmd
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("[WARN] Env file not found"));
+
+        let repo_dir = tools_dir.join("env-tool");
+        fs::write(
+            repo_dir.join(".env"),
+            "# tool settings\nGREETING=\"hello from env\"\n",
+        )
+        .unwrap();
+
+        let mut run_cmd = create_command_with_env(config_dir);
+        run_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .arg("run")
+            .arg("env-tool");
+        run_cmd.assert().success();
+
+        assert_eq!(
+            fs::read_to_string(repo_dir.join("seen.txt")).unwrap(),
+            "hello from env\n"
+        );
+    });
+}
+
+#[test]
+fn test_completion_install_writes_bash_script_under_home() {
+    with_shared_test_env(|config_dir| {
+        let expected = config_dir
+            .path()
+            .join(".local/share/bash-completion/completions/tool-meister");
+
+        let install = || {
+            let mut cmd = create_command_with_env(config_dir);
+            cmd.env_remove("XDG_DATA_HOME")
+                .env("SHELL", "/bin/bash")
+                .arg("completion")
+                .arg("install");
+            cmd
+        };
+
+        install()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(format!(
+                "[OK] Installed bash completions to {}",
+                expected.display()
+            )));
+        let script = fs::read_to_string(&expected).unwrap();
+        assert!(script.contains("tool-meister"));
+
+        install()
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("use --force to overwrite"));
+        install().arg("--force").assert().success();
+    });
+}
+
+// Register a file:// url source serving `manifest` as `tool` and install once to fill the cache
+fn cached_url_source(config_dir: &TempDir, manifest: &str, extra_args: &[&str]) {
+    let mirror_dir = config_dir.path().join("mirror");
+    fs::create_dir_all(&mirror_dir).unwrap();
+    fs::write(
+        mirror_dir.join("tool.jsonc"),
+        manifest.replace("STEP", "first"),
+    )
+    .unwrap();
+
+    let mut add_cmd = create_command_with_env(config_dir);
+    add_cmd
+        .args(["manifests", "add-source", "--source-type", "url"])
+        .args(extra_args)
+        .arg(format!("file://{}", mirror_dir.display()));
+    add_cmd.assert().success();
+
+    let mut install_cmd = create_command_with_env(config_dir);
+    install_cmd.arg("install").arg("tool");
+    install_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Step 1: first"));
+
+    fs::write(
+        mirror_dir.join("tool.jsonc"),
+        manifest.replace("STEP", "second"),
+    )
+    .unwrap();
+}
+
+const REFRESH_MANIFEST: &str = r#"{
+  "repo": { "name": "tool", "url": "https://github.com/example/tool.git", "default_branch": { "name": "main" } },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "STEP", "command": "true" }],
+    "update": [], "build": [], "run": []
+  }
+}"#;
+
+#[test]
+fn test_no_update_sources_skips_fetch_for_auto_source() {
+    with_shared_test_env(|config_dir| {
+        cached_url_source(config_dir, REFRESH_MANIFEST, &[]);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--no-update-sources").arg("install").arg("tool");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Step 1: first"));
+    });
+}
+
+#[test]
+fn test_update_sources_forces_fetch_for_manual_source() {
+    with_shared_test_env(|config_dir| {
+        cached_url_source(config_dir, REFRESH_MANIFEST, &["--no-auto-update"]);
+
+        let mut cached_cmd = create_command_with_env(config_dir);
+        cached_cmd.arg("install").arg("tool");
+        cached_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Step 1: first"));
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--update-sources").arg("install").arg("tool");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Step 1: second"));
+    });
+}
+
+#[test]
+fn test_manifests_show_lists_run_actions() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "show-tool",
+    "url": "https://github.com/example/show.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [{ "name": "make", "version": "4", "url": "https://www.gnu.org/software/make/" }],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [
+      { "seq-id": 1, "description": "Start server", "command": "./serve --port 8080" },
+      { "seq-id": 2, "description": "Open browser", "command": "xdg-open http://localhost:8080" }
+    ]
+  }
+}"#;
+        fs::write(manifest_dir.join("show-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["manifests", "show", "show-tool"]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Repository: show-tool"))
+            .stdout(predicate::str::contains("- make 4"))
+            .stdout(predicate::str::contains("Actions (run):"))
+            .stdout(predicate::str::contains(
+                "[1] Start server: ./serve --port 8080",
+            ))
+            .stdout(predicate::str::contains(
+                "[2] Open browser: xdg-open http://localhost:8080",
+            ));
+
+        let mut json_cmd = create_command_with_env(config_dir);
+        json_cmd.args(["manifests", "show", "show-tool", "--json"]);
+        json_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(r#""name": "show-tool""#));
+
+        let mut missing_cmd = create_command_with_env(config_dir);
+        missing_cmd.args(["manifests", "show", "no-such-tool"]);
+        missing_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "No manifest found for 'no-such-tool'",
+            ));
+    });
+}
+
+#[test]
+fn test_relative_tools_dir_is_independent_of_cwd() {
+    with_shared_test_env(|config_dir| {
+        let config_path = write_app_config(
+            config_dir,
+            "manifest_sources = []\ntools_dir = \"tools\"\ndefault_manifest_dir = \"manifests\"\n",
+        );
+        let config_path = config_path.as_path();
+
+        let tools_dir_from = |cwd: &Path| {
+            fs::create_dir_all(cwd).unwrap();
+            let mut cmd = create_command_with_env(config_dir);
+            let output = cmd.current_dir(cwd).arg("version").assert().success();
+            let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+            stdout
+                .lines()
+                .find_map(|line| line.strip_prefix("tools_dir: "))
+                .unwrap()
+                .to_string()
+        };
+
+        let first = tools_dir_from(&config_dir.path().join("a"));
+        let second = tools_dir_from(&config_dir.path().join("b/c"));
+        assert_eq!(first, second);
+        assert_eq!(
+            Path::new(&first),
+            config_path.parent().unwrap().join("tools")
+        );
+    });
+}
+
+#[test]
+fn test_set_overrides_repo_url_in_commands() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "override-tool",
+    "url": "https://github.com/example/override.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "Show url", "command": "echo [[repo.url]]" }],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("override-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--set")
+            .arg("repo.url=https://github.com/fork/override.git")
+            .arg("install")
+            .arg("override-tool");
+        cmd.assert().success().stdout(predicate::str::contains(
+            "Executing: echo https://github.com/fork/override.git",
+        ));
+
+        let mut invalid_cmd = create_command_with_env(config_dir);
+        invalid_cmd
+            .arg("--set")
+            .arg("repo.homepage=https://example.com")
+            .arg("install")
+            .arg("override-tool");
+        invalid_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Unsupported override path 'repo.homepage'",
+            ));
+    });
+}
+
+#[test]
+fn test_manifests_update_continues_past_failing_source() {
+    with_shared_test_env(|config_dir| {
+        let mut sources = Vec::new();
+        for name in ["first", "second", "third"] {
+            let mirror_dir = config_dir.path().join(name);
+            fs::create_dir_all(&mirror_dir).unwrap();
+            fs::write(
+                mirror_dir.join("index.json"),
+                format!(r#"["{}-tool"]"#, name),
+            )
+            .unwrap();
+            sources.push(format!("file://{}", mirror_dir.display()));
+        }
+        sources.insert(1, "http://127.0.0.1:1/manifests".to_string());
+
+        for source in &sources {
+            let mut add_cmd = create_command_with_env(config_dir);
+            add_cmd
+                .args(["manifests", "add-source", "--source-type", "url"])
+                .arg(source);
+            add_cmd.assert().success();
+        }
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["--jobs", "4", "manifests", "update"]);
+        let mut assert = cmd
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "[FAIL] update failed: url http://127.0.0.1:1/manifests",
+            ))
+            .stderr(predicate::str::contains(
+                "1 manifest source(s) failed to update",
+            ));
+        for source in sources
+            .iter()
+            .filter(|source| source.starts_with("file://"))
+        {
+            assert = assert.stdout(predicate::str::contains(format!(
+                "[OK] updated: url {} (index lists 1 tool(s), refreshed 0 cached manifest(s))",
+                source
+            )));
+        }
+
+        let indexes = config_dir.path().join(".cache/tool-meister/url-indexes");
+        assert_eq!(fs::read_dir(indexes).unwrap().count(), 3);
+    });
+}
+
+#[test]
+fn test_manifests_update_refreshes_cached_url_manifests() {
+    with_shared_test_env(|config_dir| {
+        let mirror_dir = config_dir.path().join("mirror");
+        fs::create_dir_all(&mirror_dir).unwrap();
+        fs::write(mirror_dir.join("index.json"), r#"["mirror-tool"]"#).unwrap();
+        let manifest = |url: &str| {
+            format!(
+                r#"{{
+  "repo": {{
+    "name": "mirror-tool",
+    "url": "{}",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{ "installation": [], "update": [], "build": [], "run": [] }}
+}}"#,
+                url
+            )
+        };
+        fs::write(
+            mirror_dir.join("mirror-tool.jsonc"),
+            manifest("https://example.com/old.git"),
+        )
+        .unwrap();
+
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .args(["manifests", "add-source", "--source-type", "url"])
+            .arg(format!("file://{}", mirror_dir.display()));
+        add_cmd.assert().success();
+
+        // Cache the manifest, then change it upstream
+        let mut show_cmd = create_command_with_env(config_dir);
+        show_cmd.args(["--no-update-sources", "manifests", "show", "mirror-tool"]);
+        show_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("https://example.com/old.git"));
+        fs::write(
+            mirror_dir.join("mirror-tool.jsonc"),
+            manifest("https://example.com/new.git"),
+        )
+        .unwrap();
+
+        let mut update_cmd = create_command_with_env(config_dir);
+        update_cmd.args(["manifests", "update"]);
+        update_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "index lists 1 tool(s), refreshed 1 cached manifest(s)",
+            ));
+
+        let mut show_cmd = create_command_with_env(config_dir);
+        show_cmd.args(["--no-update-sources", "manifests", "show", "mirror-tool"]);
+        show_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("https://example.com/new.git"));
+    });
+}
+
+#[test]
+fn test_duplicate_source_warns_once() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        write_app_config(
+            config_dir,
+            &format!(
+                r#"tools_dir = "tools"
+default_manifest_dir = "manifests"
+
+[[manifest_sources]]
+type = "local"
+path = "{dir}"
+
+[[manifest_sources]]
+type = "local"
+path = "{dir}/."
+"#,
+                dir = manifest_dir.display()
+            ),
+        );
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["manifests", "info"]);
+        let output = cmd.assert().success().get_output().stderr.clone();
+        let stderr = String::from_utf8(output).unwrap();
+        assert_eq!(
+            stderr
+                .matches("[WARN] Skipping manifest source 2 (")
+                .count(),
+            1,
+            "{}",
+            stderr
+        );
+    });
+}
+
+#[test]
+fn test_unknown_source_type_warns_without_breaking_resolution() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "known-tool",
+    "url": "https://github.com/example/known.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "Resolved", "command": "true" }],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("known-tool.jsonc"), test_manifest).unwrap();
+        write_app_config(
+            config_dir,
+            &format!(
+                r#"tools_dir = "tools"
+default_manifest_dir = "manifests"
+
+[[manifest_sources]]
+type = "ftp"
+path = "ftp://example.com/manifests"
+
+[[manifest_sources]]
+type = "local"
+path = "{}"
+"#,
+                manifest_dir.display()
+            ),
+        );
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("install").arg("known-tool");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Step 1: Resolved"))
+            .stdout(predicate::str::contains("unknown source type").not())
+            .stderr(predicate::str::contains(
+                "[WARN] Ignoring manifest source 1 (ftp://example.com/manifests): unknown source type 'ftp'",
+            ));
+
+        let mut list_cmd = create_command_with_env(config_dir);
+        list_cmd.args(["manifests", "list"]);
+        list_cmd.assert().success().stdout(predicate::str::contains(
+            "[WARN] unknown source type 'ftp'; this source is ignored",
+        ));
+    });
+}
+
+#[test]
+fn test_install_ref_flag_overrides_manifest_ref() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "pinned-tool",
+    "url": "https://github.com/example/pinned.git",
+    "default_branch": { "name": "main" },
+    "ref": "v1.0.0"
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "Check out", "command": "echo checkout [[repo.ref]]" }],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("pinned-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut manifest_cmd = create_command_with_env(config_dir);
+        manifest_cmd.arg("install").arg("pinned-tool");
+        manifest_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Executing: echo checkout v1.0.0"));
+
+        let mut cli_cmd = create_command_with_env(config_dir);
+        cli_cmd
+            .arg("install")
+            .arg("--ref")
+            .arg("0123abc")
+            .arg("pinned-tool");
+        cli_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Executing: echo checkout 0123abc"));
+    });
+}
+
+#[test]
+fn test_config_dir_env_var_used_without_flag() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("env-manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "env-dir-tool",
+    "url": "https://github.com/example/env-dir.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [{ "seq-id": 1, "description": "From env dir", "command": "true" }],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("env-dir-tool.jsonc"), test_manifest).unwrap();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.env("TOOL_MEISTER_CONFIG_DIR", &manifest_dir)
+            .arg("install")
+            .arg("env-dir-tool");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Step 1: From env dir"));
+
+        // The flag still takes precedence over the environment
+        let mut flag_cmd = create_command_with_env(config_dir);
+        flag_cmd
+            .env("TOOL_MEISTER_CONFIG_DIR", &manifest_dir)
+            .arg("--config-dir")
+            .arg(config_dir.path().join("elsewhere"))
+            .arg("install")
+            .arg("env-dir-tool");
+        flag_cmd.assert().failure();
+    });
+}
+
+#[test]
+fn test_manifests_show_dump_resolved_interpolates_commands() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "dump-tool",
+    "url": "https://github.com/example/dump.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "Clone", "command": "git clone -b [[repo.ref]] [[repo.url]] [[install.dir]]" }
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("dump-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let dump_path = config_dir.path().join("resolved.json");
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args([
+            "--set",
+            "repo.url=https://example.com/fork.git",
+            "manifests",
+            "show",
+            "dump-tool",
+            "--dump-resolved",
+        ])
+        .arg(&dump_path);
+        cmd.assert().success();
+
+        let dumped: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&dump_path).unwrap()).unwrap();
+        let command = dumped["actions"]["installation"][0]["command"]
+            .as_str()
+            .unwrap();
+        assert!(
+            command.starts_with("git clone -b main https://example.com/fork.git /"),
+            "unexpected command: {}",
+            command
+        );
+        assert!(
+            command.ends_with("/dump-tool"),
+            "unexpected command: {}",
+            command
+        );
+        assert_eq!(dumped["repo"]["url"], "https://example.com/fork.git");
+    });
+}
+
+#[test]
+fn test_run_print_env_includes_env_flag() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "env-tool",
+    "url": "https://github.com/example/env.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Must not run", "command": "false" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("env-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args([
+            "run",
+            "--env",
+            "GREETING=hello world",
+            "--print-env",
+            "env-tool",
+        ]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("\nGREETING=hello world\n"))
+            .stdout(predicate::str::contains("HOME="))
+            .stdout(predicate::str::contains("Executing").not());
+
+        let mut invalid_cmd = create_command_with_env(config_dir);
+        invalid_cmd.args(["run", "--env", "GREETING", "--print-env", "env-tool"]);
+        invalid_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("expected KEY=VALUE"));
+    });
+}
+
+#[test]
+fn test_install_multiple_tools_plain_output() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        for name in ["multi-a", "multi-b"] {
+            let manifest = format!(
+                r#"{{
+  "repo": {{
+    "name": "{name}",
+    "url": "https://github.com/example/{name}.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [{{ "seq-id": 1, "description": "Set up {name}", "command": "true" }}],
+    "update": [],
+    "build": [],
+    "run": []
+  }}
+}}"#
+            );
+            fs::write(manifest_dir.join(format!("{}.jsonc", name)), manifest).unwrap();
+        }
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["install", "multi-a", "multi-b"]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "[OK] Installation of multi-a completed successfully!",
+            ))
+            .stdout(predicate::str::contains(
+                "[OK] Installation of multi-b completed successfully!",
+            ))
+            .stdout(predicate::str::contains("Step 1: Set up multi-b"));
+
+        let mut missing_cmd = create_command_with_env(config_dir);
+        missing_cmd.args(["install", "multi-a", "multi-missing"]);
+        missing_cmd.assert().failure();
+
+        let mut ref_cmd = create_command_with_env(config_dir);
+        ref_cmd.args(["install", "--ref", "v1", "multi-a", "multi-b"]);
+        ref_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--ref can only be used"));
+    });
+}
+
+#[test]
+fn test_progress_counts_only_steps_that_run() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "progress-tool",
+    "url": "https://github.com/example/progress.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [
+      { "seq-id": 1, "description": "Compile", "command": "true" },
+      { "seq-id": 2, "description": "Sign", "command": "false", "os": ["plan9"] },
+      { "seq-id": 3, "description": "Package", "command": "true" }
+    ],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("progress-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.args(["install", "progress-tool"]);
+        install_cmd.assert().success();
+
+        let mut build_cmd = create_command_with_env(config_dir);
+        build_cmd.args(["build", "progress-tool"]);
+        build_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[1/2] Step 1: Compile"))
+            .stdout(predicate::str::contains("[2/2] Step 3: Package"))
+            .stdout(predicate::str::contains(
+                "Step 2: Sign\n[SKIP] Only runs on plan9",
+            ))
+            .stdout(predicate::str::contains("/3]").not());
+    });
+}
+
+#[test]
+fn test_plan_marks_os_restricted_steps() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "plan-tool",
+    "url": "https://github.com/example/plan.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [
+      { "seq-id": 1, "description": "Compile", "command": "echo building [[repo.name]]" },
+      { "seq-id": 2, "description": "Sign", "command": "false", "os": ["plan9"] }
+    ],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("plan-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["plan", "plan-tool", "build"]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Plan for plan-tool (build):"))
+            .stdout(predicate::str::contains(
+                "1. Step 1: Compile: echo building plan-tool",
+            ))
+            .stdout(predicate::str::contains(
+                "2. Step 2: Sign [SKIP] only runs on plan9",
+            ));
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.args(["install", "plan-tool"]);
+        install_cmd.assert().success();
+
+        let mut build_cmd = create_command_with_env(config_dir);
+        build_cmd.args(["build", "plan-tool"]);
+        build_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[SKIP] Only runs on plan9"));
+
+        let mut invalid_cmd = create_command_with_env(config_dir);
+        invalid_cmd.args(["plan", "plan-tool", "deploy"]);
+        invalid_cmd.assert().failure();
+    });
+}
+
+#[test]
+fn test_workspace_dir_token_expands_to_cwd() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "workspace-tool",
+    "url": "https://github.com/example/workspace.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [{ "seq-id": 1, "description": "Show workspace", "command": "echo ws=[[workspace.dir]]" }],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("workspace-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let workspace = config_dir.path().join("workspace");
+        fs::create_dir_all(&workspace).unwrap();
+        let workspace = workspace.canonicalize().unwrap();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.current_dir(&workspace)
+            .args(["plan", "workspace-tool", "build"]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(format!(
+                "echo ws={}",
+                workspace.display()
+            )));
+    });
+}
+
+#[test]
+fn test_run_detach_writes_log_and_pid_file() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "detach-tool",
+    "url": "https://github.com/example/detach.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Serve", "command": "echo serving" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("detach-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.args(["install", "detach-tool"]);
+        install_cmd.assert().success();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["run", "--detach", "detach-tool"]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Log: "))
+            .stdout(predicate::str::contains("PID file: "))
+            .stdout(predicate::str::contains("Spawned: Serve"));
+
+        let run_dir = config_dir
+            .path()
+            .join(".cache/tool-meister/runs/detach-tool");
+        let pid = fs::read_to_string(run_dir.join("1.pid")).unwrap();
+        assert!(pid.trim().parse::<u32>().unwrap() > 0);
+
+        // The detached process writes its log on its own schedule
+        let log_path = run_dir.join("1.log");
+        let mut log = String::new();
+        for _ in 0..50 {
+            log = fs::read_to_string(&log_path).unwrap_or_default();
+            if log.contains("serving") {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        assert!(log.contains("serving"), "log: {:?}", log);
+    });
+}
+
+#[test]
+fn test_run_no_cd_uses_invocation_directory() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "cwd-tool",
+    "url": "https://github.com/example/cwd.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Mark cwd", "command": "pwd > ran-here" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("cwd-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.args(["install", "cwd-tool"]);
+        install_cmd.assert().success();
+
+        let invocation_dir = config_dir.path().join("invocation");
+        fs::create_dir_all(&invocation_dir).unwrap();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.current_dir(&invocation_dir)
+            .args(["run", "--no-cd", "cwd-tool"]);
+        cmd.assert().success();
+
+        let recorded = fs::read_to_string(invocation_dir.join("ran-here")).unwrap();
+        assert_eq!(
+            Path::new(recorded.trim()).canonicalize().unwrap(),
+            invocation_dir.canonicalize().unwrap()
+        );
+    });
+}
+
+#[test]
+fn test_build_no_cd_keeps_git_pull_in_the_repository() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("pull-tools");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "pull-tool",
+    "url": "https://github.com/example/pull.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [{ "seq-id": 1, "description": "Pull", "type": "git_pull" }],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("pull-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .args(["install", "pull-tool"]);
+        install_cmd.assert().success();
+
+        // The pull itself fails (the directory is no clone); it must not run in our cwd
+        let mut build_cmd = create_command_with_env(config_dir);
+        build_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .arg("--trace-commands")
+            .args(["build", "--no-cd", "pull-tool"]);
+        build_cmd.assert().stderr(predicate::str::contains(format!(
+            "[trace] step 1 start: cwd={} ",
+            tools_dir.join("pull-tool").display()
+        )));
+    });
+}
+
+#[test]
+fn test_manifests_info_check_reports_parse_errors() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let valid_manifest = r#"{
+  "repo": {
+    "name": "healthy-tool",
+    "url": "https://github.com/example/healthy.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        fs::write(manifest_dir.join("healthy-tool.jsonc"), valid_manifest).unwrap();
+        fs::write(
+            manifest_dir.join("broken-tool.jsonc"),
+            r#"{ "repo": { "name": "broken-tool" "#,
+        )
+        .unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["manifests", "info", "--check"]);
+        cmd.assert()
+            .failure()
+            .stdout(predicate::str::contains("[OK] reachable"))
+            .stdout(predicate::str::contains("[OK] healthy-tool"))
+            .stdout(predicate::str::contains("[FAIL] broken-tool: "))
+            .stdout(predicate::str::contains("Failed to parse config file"))
+            .stderr(predicate::str::contains("problem(s) found"));
+
+        // Listing options don't apply to a check
+        for option in [
+            &["--all-sources"][..],
+            &["--limit", "1"],
+            &["--sort", "name"],
+        ] {
+            let mut conflict_cmd = create_command_with_env(config_dir);
+            conflict_cmd
+                .args(["manifests", "info", "--check"])
+                .args(option);
+            conflict_cmd
+                .assert()
+                .failure()
+                .code(2)
+                .stderr(predicate::str::contains("cannot be used with"));
+        }
+    });
+}
+
+#[test]
+fn test_spawn_default_applies_to_listed_phase_only() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "spawn-default-tool",
+    "url": "https://github.com/example/spawn-default.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "spawn_default": ["run"],
+    "installation": [],
+    "update": [],
+    "build": [{ "seq-id": 1, "description": "Compile", "command": "true" }],
+    "run": [{ "seq-id": 1, "description": "Launch", "command": "true" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("spawn-default-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.args(["install", "spawn-default-tool"]);
+        install_cmd.assert().success();
+
+        let mut run_cmd = create_command_with_env(config_dir);
+        run_cmd.args(["run", "spawn-default-tool"]);
+        run_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Spawned: Launch"));
+
+        let mut build_cmd = create_command_with_env(config_dir);
+        build_cmd.args(["build", "spawn-default-tool"]);
+        build_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Completed: Compile"))
+            .stdout(predicate::str::contains("Spawned").not());
+    });
+}
+
+#[test]
+fn test_install_reports_stable_size_and_hash() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("size-tools");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "size-tool",
+    "url": "https://github.com/example/size.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "write", "command": "echo hello > [[install.dir]]/file.txt", "shell": "bash" }
+    ],
+    "update": [],
+    "build": [],
+    "run": []
+  }
+}"#;
+        fs::write(manifest_dir.join("size-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let summary = || {
+            let mut cmd = create_command_with_env(config_dir);
+            cmd.arg("--tools-dir").arg(&tools_dir).args([
+                "install",
+                "size-tool",
+                "--reinstall",
+                "--hash",
+            ]);
+            let output = cmd.assert().success().get_output().stdout.clone();
+            String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .filter(|line| {
+                    line.starts_with("Install size:") || line.starts_with("Content hash:")
+                })
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        let first = summary();
+        assert_eq!(first.len(), 2, "missing summary lines: {:?}", first);
+        assert_eq!(first[0], "Install size: 6 B in 1 files");
+        assert!(first[1].starts_with("Content hash: sha256:"));
+        assert_eq!(summary(), first);
+    });
+}
+
+#[test]
+fn test_chdir_resolves_relative_paths_from_target_directory() {
+    with_shared_test_env(|config_dir| {
+        let workdir = config_dir.path().join("chdir-work");
+        let manifest_dir = workdir.join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "chdir-tool",
+    "url": "https://github.com/example/chdir.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": { "installation": [], "update": [], "build": [], "run": [] }
+}"#;
+        fs::write(manifest_dir.join("chdir-tool.jsonc"), test_manifest).unwrap();
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--chdir")
+            .arg(&workdir)
+            .args(["--config-dir", "manifests", "--tools-dir", "tools"])
+            .args(["install", "chdir-tool"]);
+        cmd.assert().success();
+
+        assert!(workdir.join("tools/chdir-tool").is_dir());
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("--chdir")
+            .arg(config_dir.path().join("missing"))
+            .args(["install", "chdir-tool"]);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("--chdir: not a directory"));
+    });
+}
+
+#[test]
+fn test_run_forwards_dash_c_after_the_tool_name() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "make-tool",
+    "url": "https://github.com/example/make.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "Echo arguments", "command": "echo" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("make-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd.args(["install", "make-tool"]);
+        install_cmd.assert().success();
+
+        // `-C` after the tool name is the tool's, not our --chdir
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["run", "make-tool", "-C", "missing-dir", "make"]);
+        cmd.assert().success().stdout(predicate::str::contains(
+            "Executing: echo -C missing-dir make\n",
+        ));
+    });
+}
+
+#[test]
+fn test_exit_codes_distinguish_missing_tool_and_failing_command() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("exit-code-tools");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "exit-code-tool",
+    "url": "https://github.com/example/exit-code.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{ "seq-id": 1, "description": "fail", "command": "exit 7", "shell": "bash" }]
+  }
+}"#;
+        fs::write(manifest_dir.join("exit-code-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let tool = |args: &[&str]| {
+            let mut cmd = create_command_with_env(config_dir);
+            cmd.arg("--tools-dir").arg(&tools_dir).args(args);
+            cmd.assert()
+        };
+
+        tool(&["install", "exit-code-tool"]).success();
+        tool(&["run", "exit-code-tool"])
+            .failure()
+            .code(4)
+            .stderr(predicate::str::contains("Command failed"));
+        tool(&["run", "no-such-tool"])
+            .failure()
+            .code(3)
+            .stderr(predicate::str::contains(
+                "No manifest found for 'no-such-tool'",
+            ));
+        tool(&["run", "../escape"]).failure().code(2);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sigterm_is_forwarded_to_waited_child() {
+    use std::time::{Duration, Instant};
+
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("signal-tools");
+        let ready = config_dir.path().join("signal-ready");
+        let received = config_dir.path().join("signal-received");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "signal-tool",
+    "url": "https://github.com/example/signal.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [{{
+      "seq-id": 1,
+      "description": "wait for a signal",
+      "command": "trap 'touch {received}; exit 0' TERM; touch {ready}; sleep 30 & wait",
+      "shell": "bash"
+    }}]
+  }}
+}}"#,
+            received = received.display(),
+            ready = ready.display()
+        );
+        fs::write(manifest_dir.join("signal-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .args(["install", "signal-tool"]);
+        install_cmd.assert().success();
+
+        let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("tool-meister"))
+            .env("XDG_CONFIG_HOME", config_dir.path())
+            .env("XDG_CACHE_HOME", config_dir.path().join(".cache"))
+            .env("HOME", config_dir.path())
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .args(["run", "signal-tool"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !ready.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(ready.exists(), "the run action never started");
+
+        let signalled = Instant::now();
+        std::process::Command::new("kill")
+            .args(["-TERM", &child.id().to_string()])
+            .status()
+            .unwrap();
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            if signalled.elapsed() > Duration::from_secs(10) {
+                child.kill().unwrap();
+                panic!("tool-meister did not exit after SIGTERM");
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        };
+
+        assert!(received.exists(), "the child did not receive SIGTERM");
+        assert_eq!(status.code(), Some(143));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sigterm_stops_interactive_step_after_earlier_waited_step() {
+    use std::time::{Duration, Instant};
+
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("signal-tools");
+        let ready = config_dir.path().join("sleep-ready");
+
+        let test_manifest = format!(
+            r#"{{
+  "repo": {{
+    "name": "sleep-tool",
+    "url": "https://github.com/example/sleep.git",
+    "default_branch": {{ "name": "main" }}
+  }},
+  "dependencies": [],
+  "actions": {{
+    "installation": [],
+    "update": [],
+    "build": [],
+    "run": [
+      {{ "seq-id": 1, "description": "warm up", "command": "true" }},
+      {{ "seq-id": 2, "description": "sleep", "command": "touch {ready} && sleep 6", "interactive": true }}
+    ]
+  }}
+}}"#,
+            ready = ready.display()
+        );
+        fs::write(manifest_dir.join("sleep-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut install_cmd = create_command_with_env(config_dir);
+        install_cmd
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .args(["install", "sleep-tool"]);
+        install_cmd.assert().success();
+
+        let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("tool-meister"))
+            .env("XDG_CONFIG_HOME", config_dir.path())
+            .env("XDG_CACHE_HOME", config_dir.path().join(".cache"))
+            .env("HOME", config_dir.path())
+            .arg("--tools-dir")
+            .arg(&tools_dir)
+            .args(["run", "sleep-tool"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !ready.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(ready.exists(), "the interactive step never started");
+
+        let signalled = Instant::now();
+        std::process::Command::new("kill")
+            .args(["-TERM", &child.id().to_string()])
+            .status()
+            .unwrap();
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            if signalled.elapsed() > Duration::from_secs(10) {
+                child.kill().unwrap();
+                panic!("tool-meister did not exit after SIGTERM");
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        };
+
+        assert!(
+            signalled.elapsed() < Duration::from_secs(3),
+            "SIGTERM was ignored"
+        );
+        assert_eq!(status.code(), Some(143));
+    });
+}
+
+#[test]
+fn test_manifests_new_from_local_starter() {
+    with_shared_test_env(|config_dir| {
+        let starter_dir = config_dir.path().join("starters");
+        let manifest_dir = config_dir.path().join("new-manifests");
+        fs::create_dir_all(&starter_dir).unwrap();
+
+        let starter = r#"{
+  "repo": {
+    "name": "{{name}}",
+    "url": "{{url}}",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [],
+    "update": [],
+    "build": [{ "seq-id": 1, "description": "build", "command": "make [[repo.name]]" }],
+    "run": []
+  }
+}"#;
+        let starter_path = starter_dir.join("rust-cli.jsonc");
+        fs::write(&starter_path, starter).unwrap();
+
+        let new_manifest = |extra: &[&str]| {
+            let mut cmd = create_command_with_env(config_dir);
+            cmd.arg("--config-dir")
+                .arg(&manifest_dir)
+                .args(["manifests", "new", "starter-tool", "--from"])
+                .arg(&starter_path)
+                .args(extra);
+            cmd.assert()
+        };
+
+        new_manifest(&["--url", "https://example.com/starter-tool.git"])
+            .success()
+            .stdout(predicate::str::contains(
+                "[OK] Created manifest for starter-tool",
+            ));
+
+        let written = fs::read_to_string(manifest_dir.join("starter-tool.jsonc")).unwrap();
+        assert!(written.contains(r#""name": "starter-tool""#));
+        assert!(written.contains("https://example.com/starter-tool.git"));
+        assert!(written.contains("make [[repo.name]]"));
+
+        let mut show_cmd = create_command_with_env(config_dir);
+        show_cmd
+            .arg("--config-dir")
+            .arg(&manifest_dir)
+            .args(["plan", "starter-tool", "build"]);
+        show_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("make starter-tool"));
+
+        new_manifest(&["--url", "https://example.com/other.git"])
+            .failure()
+            .stderr(predicate::str::contains("--force"));
+        new_manifest(&["--force"])
+            .failure()
+            .stderr(predicate::str::contains("pass --url"));
+    });
+}
+
+#[test]
+fn test_run_without_tool_requires_name_when_not_interactive() {
+    with_shared_test_env(|config_dir| {
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.arg("run");
+        cmd.assert()
+            .failure()
+            .code(2)
+            .stderr(predicate::str::contains("a tool name is required"));
+    });
+}
+
+#[test]
+fn test_verbose_reports_manifest_provenance() {
+    with_shared_test_env(|config_dir| {
+        let manifest = |name: &str| {
+            format!(
+                r#"{{
+  "repo": {{ "name": "{}", "url": "https://github.com/example/{}.git", "default_branch": {{ "name": "main" }} }},
+  "dependencies": [],
+  "actions": {{ "installation": [], "update": [], "build": [], "run": [] }}
+}}"#,
+                name, name
+            )
+        };
+
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::write(
+            manifest_dir.join("local-tool.jsonc"),
+            manifest("local-tool"),
+        )
+        .unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd.args([
+            "manifests",
+            "add-source",
+            "--source-type",
+            "git",
+            "--no-auto-update",
+            "https://github.com/example/provenance.git",
+        ]);
+        add_cmd.assert().success();
+        let cache_dir = config_dir
+            .path()
+            .join(".cache/tool-meister/https___github_com_example_provenance_git");
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join("git-tool.jsonc"), manifest("git-tool")).unwrap();
+
+        let plan = |tool: &str, verbose: bool| {
+            let mut cmd = create_command_with_env(config_dir);
+            if verbose {
+                cmd.arg("--verbose");
+            }
+            cmd.args(["plan", tool, "run"]);
+            cmd.assert().success()
+        };
+
+        plan("local-tool", true).stderr(predicate::str::contains(format!(
+            "[debug] Using manifest for local-tool from local:{} (source 2)",
+            manifest_dir.display()
+        )));
+        plan("git-tool", true).stderr(predicate::str::contains(
+            "[debug] Using manifest for git-tool from git:https://github.com/example/provenance.git (source 3, cached)",
+        ));
+        plan("git-tool", false).stderr(predicate::str::contains("Using manifest").not());
+    });
+}
+
+#[test]
+fn test_manifests_info_limit_truncates_listing() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("many-manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        for index in (1..=12).rev() {
+            fs::write(manifest_dir.join(format!("tool-{:02}.jsonc", index)), "{}").unwrap();
+        }
+        add_local_source(config_dir, &manifest_dir);
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["manifests", "info", "--source", "many-manifests"])
+            .args(["--limit", "5", "--sort", "name"]);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let stdout = String::from_utf8(output).unwrap();
+
+        let listed: Vec<&str> = stdout
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("- "))
+            .collect();
+        assert_eq!(
+            listed,
+            vec!["tool-01", "tool-02", "tool-03", "tool-04", "tool-05"]
+        );
+        assert!(stdout.contains("... and 7 more"));
+
+        let mut unlimited = create_command_with_env(config_dir);
+        unlimited.args(["manifests", "info", "--source", "many-manifests"]);
+        unlimited
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("- tool-12"))
+            .stdout(predicate::str::contains("more").not());
+    });
+}
+
+#[test]
+fn test_verify_phase_checks_installed_binary() {
+    with_shared_test_env(|config_dir| {
+        let manifest_dir = config_dir.path().join("manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let tools_dir = config_dir.path().join("verify-tools");
+
+        let test_manifest = r#"{
+  "repo": {
+    "name": "verify-tool",
+    "url": "https://github.com/example/verify.git",
+    "default_branch": { "name": "main" }
+  },
+  "dependencies": [],
+  "actions": {
+    "installation": [
+      { "seq-id": 1, "description": "produce binary", "command": "mkdir -p [[install.dir]]/bin && touch [[install.dir]]/bin/verify-tool", "shell": "bash" }
+    ],
+    "update": [],
+    "build": [],
+    "run": [],
+    "verify": [
+      { "seq-id": 1, "description": "binary exists", "command": "test -f bin/verify-tool", "shell": "bash" }
+    ]
+  }
+}"#;
+        fs::write(manifest_dir.join("verify-tool.jsonc"), test_manifest).unwrap();
+        add_local_source(config_dir, &manifest_dir);
+
+        let tool = |args: &[&str]| {
+            let mut cmd = create_command_with_env(config_dir);
+            cmd.arg("--tools-dir").arg(&tools_dir).args(args);
+            cmd.assert()
+        };
+
+        tool(&["verify", "verify-tool"])
+            .failure()
+            .code(3)
+            .stderr(predicate::str::contains("not installed"));
+
+        tool(&["install", "verify-tool", "--verify"])
+            .success()
+            .stdout(predicate::str::contains("Verifying verify-tool..."));
+        tool(&["verify", "verify-tool"])
+            .success()
+            .stdout(predicate::str::contains(
+                "[OK] Verification of verify-tool passed!",
+            ));
+
+        fs::remove_file(tools_dir.join("verify-tool/bin/verify-tool")).unwrap();
+        tool(&["verify", "verify-tool"])
+            .failure()
+            .code(4)
+            .stderr(predicate::str::contains("test -f bin/verify-tool"));
+    });
+}
+
+#[test]
+fn test_locked_mode_never_writes_app_config() {
+    with_shared_test_env(|config_dir| {
+        let config_path = config_dir.path().join("tool-meister/config.toml");
+
+        let mut cmd = create_command_with_env(config_dir);
+        cmd.args(["--locked", "manifests", "list"]);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("locked mode forbids creating one"));
+        assert!(!config_path.exists());
+
+        let mut env_cmd = create_command_with_env(config_dir);
+        env_cmd
+            .env("TOOL_MEISTER_LOCKED", "1")
+            .args(["manifests", "list"]);
+        env_cmd.assert().failure();
+        assert!(!config_path.exists());
+
+        let mut create_cmd = create_command_with_env(config_dir);
+        create_cmd.args(["manifests", "list"]);
+        create_cmd.assert().success();
+        let original = fs::read_to_string(&config_path).unwrap();
+
+        let manifest_dir = config_dir.path().join("locked-manifests");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let mut add_cmd = create_command_with_env(config_dir);
+        add_cmd
+            .args([
+                "--locked",
+                "manifests",
+                "add-source",
+                "--source-type",
+                "local",
+            ])
+            .arg(&manifest_dir);
+        add_cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("The app configuration is locked"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+
+        let mut list_cmd = create_command_with_env(config_dir);
+        list_cmd.args(["--locked", "manifests", "list"]);
+        list_cmd.assert().success();
+    });
+}